@@ -0,0 +1,240 @@
+use clap::{Args, Parser, Subcommand};
+use fplot::export::pdf::PageSetup;
+use fplot::renderer::device::DeviceSelector;
+use fplot::renderer::present_mode::{PresentMode, PRESENT_MODE_NAMES};
+use nalgebra::Vector2;
+use std::path::PathBuf;
+
+/**
+Function plotter, opens an interactive window unless a subcommand is given.
+
+Exits with 0 on success, 1 when the expressions, the data files or the theme are invalid or the
+plot cannot be rendered and 2 when the arguments are invalid.
+*/
+#[derive(Parser)]
+#[clap(name = "fplot", version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+    /// Lists the devices found, which one would draw the plots and why the others cannot
+    #[clap(long)]
+    pub list_devices: bool,
+    /// Prints the Vulkan version, layers and extensions and what each device supports, as text
+    /// or as json with --vulkan-info=json
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        min_values = 0,
+        multiple_values = false,
+        require_equals = true,
+        default_missing_value = "text",
+        parse(try_from_str = parse_report_format)
+    )]
+    pub vulkan_info: Option<ReportFormat>,
+    #[clap(flatten)]
+    pub window: WindowArgs,
+}
+
+/// Format of the report printed by --vulkan-info
+#[derive(Clone, Copy)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+pub struct WindowArgs {
+    /// Functions of x to plot, like "sin(x) * x", cos(x) is plotted when neither expressions nor
+    /// data files are given
+    #[clap(value_name = "EXPRESSION")]
+    pub expressions: Vec<String>,
+    /// File with the points of a data series, one per line as x and y separated by spaces, tabs,
+    /// commas or semicolons. Can be repeated
+    #[clap(long = "data", value_name = "FILE", number_of_values = 1)]
+    pub data_files: Vec<PathBuf>,
+    /// Initial interval of x, the y one is fitted to the curves unless given too. Like the other
+    /// options of the window, it takes precedence over the config file
+    #[clap(
+        long = "x",
+        value_name = "START..END",
+        allow_hyphen_values = true,
+        parse(try_from_str = parse_range)
+    )]
+    pub x_range: Option<(f32, f32)>,
+    /// Initial interval of y
+    #[clap(
+        long = "y",
+        value_name = "START..END",
+        allow_hyphen_values = true,
+        parse(try_from_str = parse_range)
+    )]
+    pub y_range: Option<(f32, f32)>,
+    /// Size of the window [default: 800x800]
+    #[clap(long, value_name = "WIDTHxHEIGHT", parse(try_from_str = parse_size))]
+    pub size: Option<(u32, u32)>,
+    /// Opens the window fullscreen on the current monitor
+    #[clap(long)]
+    pub fullscreen: bool,
+    /// Opens a window even when the config file selects fullscreen
+    #[clap(long, conflicts_with = "fullscreen")]
+    pub windowed: bool,
+    /// How the frames are presented: fifo (vsync), fifo_relaxed, mailbox or immediate (may
+    /// tear), unsupported modes fall back to fifo [default: mailbox]
+    #[clap(long, value_name = "MODE", parse(try_from_str = parse_present_mode))]
+    pub present_mode: Option<PresentMode>,
+    /// Samples per pixel used for anti-aliasing: 1 (disabled), 2, 4 or 8, lowered to what the GPU
+    /// supports [default: 4]
+    #[clap(long, value_name = "SAMPLES", parse(try_from_str = parse_msaa))]
+    pub msaa: Option<u32>,
+    /// Theme of the plot, the name of a built-in or custom theme or the path of a theme file
+    #[clap(long, value_name = "THEME")]
+    pub theme: Option<String>,
+    /// Device that draws the plot instead of the best one: its index in --list-devices, its
+    /// vendor ID in hexadecimal like 0x10de or part of its name. Takes precedence over the
+    /// FPLOT_DEVICE environment variable
+    #[clap(long, value_name = "DEVICE", parse(try_from_str = DeviceSelector::parse))]
+    pub device: Option<DeviceSelector>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Renders a plot to a PNG image, an SVG file or a PDF document without opening a window, or
+    /// an animation to a GIF image or a sequence of PNG images
+    Render(RenderArgs),
+}
+
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Function of x and of the time t to plot, like "sin(x - t) * x"
+    #[clap(long = "expr", value_name = "EXPRESSION")]
+    pub expression: String,
+    /// Interval of x shown in the image, the y one is fitted to the curve
+    #[clap(
+        long = "x",
+        value_name = "START..END",
+        default_value = "-10..10",
+        allow_hyphen_values = true,
+        parse(try_from_str = parse_range)
+    )]
+    pub x_range: (f32, f32),
+    /// Size of the image in pixels, PDF documents use the page size instead
+    #[clap(
+        long,
+        value_name = "WIDTHxHEIGHT",
+        default_value = "1920x1080",
+        parse(try_from_str = parse_size)
+    )]
+    pub size: (u32, u32),
+    /// Size of the PDF page: a3, a4, a5, letter or legal, optionally followed by -landscape, or
+    /// WIDTHxHEIGHT in millimeters [default: a4-landscape]
+    #[clap(long, value_name = "SIZE", parse(try_from_str = PageSetup::parse_size))]
+    pub page: Option<Vector2<f64>>,
+    /// Width of the margins of the PDF page in millimeters [default: 15]
+    #[clap(long, value_name = "MILLIMETERS", parse(try_from_str = parse_margin))]
+    pub margin: Option<f64>,
+    /// Number of frames of the animation, each one is drawn at the time of the previous one
+    /// increased by the time step, starting from 0
+    #[clap(long, value_name = "COUNT", default_value = "1", parse(try_from_str = parse_count))]
+    pub frames: u32,
+    /// Increment of the time t between two frames, which is also how long each frame of a GIF
+    /// image is shown for
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        default_value = "0.04",
+        parse(try_from_str = parse_time_step)
+    )]
+    pub time_step: f32,
+    /// Path of the output, which is an SVG file when its extension is svg, a PDF document when it
+    /// is pdf, a GIF image when it is gif and a PNG image otherwise. The frames of an animation
+    /// saved as PNG get the number of the frame appended to the name, like plot-0001.png
+    #[clap(short, long, value_name = "FILE")]
+    pub output: PathBuf,
+    /// Device that draws the plot, like the option of the window
+    #[clap(long, value_name = "DEVICE", parse(try_from_str = DeviceSelector::parse))]
+    pub device: Option<DeviceSelector>,
+}
+
+/// Parses an interval like `-10..10`, whose start must be lower than its end
+fn parse_range(text: &str) -> Result<(f32, f32), String> {
+    let (start, end) = text
+        .split_once("..")
+        .ok_or_else(|| "expected START..END".to_string())?;
+    let parse = |bound: &str| {
+        bound
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|bound| bound.is_finite())
+            .ok_or_else(|| format!("{} is not a number", bound))
+    };
+    let (start, end) = (parse(start)?, parse(end)?);
+    if start >= end {
+        return Err(format!("{} is not lower than {}", start, end));
+    }
+    Ok((start, end))
+}
+
+/// Parses a size like `1920x1080`, both sides must be at least one pixel
+fn parse_size(text: &str) -> Result<(u32, u32), String> {
+    let (width, height) = text
+        .split_once('x')
+        .ok_or_else(|| "expected WIDTHxHEIGHT".to_string())?;
+    let parse = |side: &str| {
+        side.trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|side| *side > 0)
+            .ok_or_else(|| format!("{} is not a positive integer", side))
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+/// Parses a margin in millimeters, which cannot be negative
+fn parse_margin(text: &str) -> Result<f64, String> {
+    text.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|margin| margin.is_finite() && *margin >= 0.0f64)
+        .ok_or_else(|| format!("{} is not a non negative number", text))
+}
+
+/// Parses a count, which must be at least one
+fn parse_count(text: &str) -> Result<u32, String> {
+    text.trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| format!("{} is not a positive integer", text))
+}
+
+/// Parses a time step, which must be a positive number of seconds
+fn parse_time_step(text: &str) -> Result<f32, String> {
+    text.trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|step| step.is_finite() && *step > 0.0f32)
+        .ok_or_else(|| format!("{} is not a positive number", text))
+}
+
+/// Parses a number of samples per pixel, which can be 1, 2, 4 or 8
+fn parse_msaa(text: &str) -> Result<u32, String> {
+    text.trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|samples| [1, 2, 4, 8].contains(samples))
+        .ok_or_else(|| "expected 1, 2, 4 or 8".to_string())
+}
+
+fn parse_present_mode(text: &str) -> Result<PresentMode, String> {
+    PresentMode::from_name(text)
+        .ok_or_else(|| format!("expected one of {}", PRESENT_MODE_NAMES.join(", ")))
+}
+
+fn parse_report_format(text: &str) -> Result<ReportFormat, String> {
+    match text {
+        "text" => Ok(ReportFormat::Text),
+        "json" => Ok(ReportFormat::Json),
+        _ => Err("expected text or json".to_string()),
+    }
+}
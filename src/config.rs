@@ -0,0 +1,348 @@
+use crate::export::pdf::PageSetup;
+use crate::keybindings::KeyBindings;
+use crate::renderer::present_mode::{PresentMode, PRESENT_MODE_NAMES};
+use crate::renderer::style::{DashPattern, LineStyle};
+use crate::theme::{self, Theme};
+use crate::window::WindowSettings;
+use std::path::PathBuf;
+
+pub const DEFAULT_MSAA_SAMPLES: u32 = 4;
+
+const KEYS: [&str; 9] = [
+    "msaa",
+    "theme",
+    "grid",
+    "colors",
+    "keybindings",
+    "curves",
+    "window",
+    "view",
+    "pdf",
+];
+
+/// Location of the config file, `$XDG_CONFIG_HOME/fplot/config.toml` on Linux
+pub fn config_file_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("fplot").join("config.toml"))
+}
+
+/**
+Reads and parses the config file. A missing file is not an error and results in an empty table,
+while a file that cannot be read or parsed is reported with a warning and then ignored, like the
+unknown keys.
+*/
+pub fn load_config_table() -> toml::value::Table {
+    let path = match config_file_path() {
+        Some(path) if path.exists() => path,
+        _ => return toml::value::Table::new(),
+    };
+    let parsed = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| contents.parse::<toml::Value>().map_err(|e| e.to_string()));
+    match parsed {
+        Ok(toml::Value::Table(table)) => {
+            warn_unknown_keys(&table, "", &KEYS);
+            table
+        }
+        Ok(_) => toml::value::Table::new(),
+        Err(e) => {
+            eprintln!("Warning: ignoring config file {}: {}", path.display(), e);
+            toml::value::Table::new()
+        }
+    }
+}
+
+/// Reports the keys of a table that are not among the known ones, `prefix` is the path of the table
+fn warn_unknown_keys(table: &toml::value::Table, prefix: &str, known: &[&str]) {
+    for key in table.keys().filter(|key| !known.contains(&key.as_str())) {
+        eprintln!("Warning: {}{}: unknown key", prefix, key);
+    }
+}
+
+/// The table with the given name, a value of another type is reported with a warning and ignored
+fn table<'a>(config: &'a toml::value::Table, name: &str) -> Option<&'a toml::value::Table> {
+    match config.get(name)? {
+        toml::Value::Table(table) => Some(table),
+        value => {
+            eprintln!("Warning: {}: expected a table but found {}", name, value);
+            None
+        }
+    }
+}
+
+/// Samples per pixel requested with the `msaa` key, which can be 1 (disabled), 2, 4 or 8
+pub fn msaa_samples(config: &toml::value::Table) -> u32 {
+    match config.get("msaa") {
+        None => DEFAULT_MSAA_SAMPLES,
+        Some(toml::Value::Integer(samples)) if [1, 2, 4, 8].contains(samples) => *samples as u32,
+        Some(value) => {
+            eprintln!(
+                "Warning: msaa: expected 1, 2, 4 or 8 but found {}, using {}",
+                value, DEFAULT_MSAA_SAMPLES
+            );
+            DEFAULT_MSAA_SAMPLES
+        }
+    }
+}
+
+/// Whether the grid is shown at startup, from the `grid` key
+pub fn grid_visible(config: &toml::value::Table) -> bool {
+    match config.get("grid") {
+        None => true,
+        Some(toml::Value::Boolean(visible)) => *visible,
+        Some(value) => {
+            eprintln!("Warning: grid: expected true or false but found {}", value);
+            true
+        }
+    }
+}
+
+/**
+Index among the given themes of the selected one, which is the given name or the one of the
+`theme` key, either the name of a built-in or custom theme or the path of a theme file. A given
+name that is not found is an error, while the config one is reported and replaced by the first
+theme. The colors of the `[colors]` table then replace the ones of the selected theme.
+*/
+pub fn select_theme(
+    config: &toml::value::Table,
+    themes: &mut Vec<Theme>,
+    name: Option<&str>,
+) -> Result<usize, String> {
+    let index = match (name, config.get("theme")) {
+        (Some(name), _) => theme::select_theme(name, themes)?,
+        (None, None) => 0,
+        (None, Some(value)) => value
+            .as_str()
+            .ok_or_else(|| format!("theme: expected a string but found {}", value))
+            .and_then(|name| theme::select_theme(name, themes))
+            .unwrap_or_else(|e| {
+                eprintln!("Warning: {}, using {}", e, themes[0].name);
+                0
+            }),
+    };
+    if let Some(colors) = table(config, "colors") {
+        for warning in themes[index].apply_colors(colors) {
+            eprintln!("Warning: colors.{}", warning);
+        }
+    }
+    Ok(index)
+}
+
+/// Key bindings with the changes of the `[keybindings]` table applied
+pub fn key_bindings(config: &toml::value::Table) -> KeyBindings {
+    let mut key_bindings = KeyBindings::default();
+    if let Some(table) = table(config, "keybindings") {
+        for warning in key_bindings.apply_table(table) {
+            eprintln!("Warning: {}", warning);
+        }
+    }
+    key_bindings
+}
+
+/**
+Settings of the window from the `[window]` table, where `size = [width, height]` is in pixels,
+`fullscreen` is a boolean and `present_mode` is one of `"fifo"`, `"fifo_relaxed"`, `"mailbox"` and
+`"immediate"`. Invalid values are reported with a warning and replaced by the default ones.
+*/
+pub fn window_settings(config: &toml::value::Table) -> WindowSettings {
+    let mut settings = WindowSettings::default();
+    let table = match table(config, "window") {
+        Some(table) => table,
+        None => return settings,
+    };
+    warn_unknown_keys(table, "window.", &["size", "fullscreen", "present_mode"]);
+    if let Some(value) = table.get("size") {
+        let side = |side: &toml::Value| {
+            side.as_integer()
+                .and_then(|side| u32::try_from(side).ok())
+                .filter(|side| *side > 0)
+        };
+        let size = match value.as_array().map(Vec::as_slice) {
+            Some([width, height]) => side(width).zip(side(height)),
+            _ => None,
+        };
+        match size {
+            Some(size) => settings.size = size,
+            None => eprintln!(
+                "Warning: window.size: expected [width, height] in pixels but found {}",
+                value
+            ),
+        }
+    }
+    if let Some(value) = table.get("fullscreen") {
+        match value.as_bool() {
+            Some(fullscreen) => settings.fullscreen = fullscreen,
+            None => eprintln!(
+                "Warning: window.fullscreen: expected true or false but found {}",
+                value
+            ),
+        }
+    }
+    if let Some(value) = table.get("present_mode") {
+        match value.as_str().and_then(PresentMode::from_name) {
+            Some(present_mode) => settings.present_mode = present_mode,
+            None => eprintln!(
+                "Warning: window.present_mode: expected one of {} but found {}",
+                PRESENT_MODE_NAMES.join(", "),
+                value
+            ),
+        }
+    }
+    settings
+}
+
+/// Initial intervals of the view, the y one is fitted to the curves when missing
+#[derive(Default)]
+pub struct ViewSettings {
+    pub x_range: Option<(f32, f32)>,
+    pub y_range: Option<(f32, f32)>,
+}
+
+/**
+Initial view from the `[view]` table, where `x = [start, end]` and `y = [start, end]` are the
+intervals shown. Invalid values are reported with a warning and ignored.
+*/
+pub fn view_settings(config: &toml::value::Table) -> ViewSettings {
+    let mut settings = ViewSettings::default();
+    let table = match table(config, "view") {
+        Some(table) => table,
+        None => return settings,
+    };
+    warn_unknown_keys(table, "view.", &["x", "y"]);
+    for (key, range) in [("x", &mut settings.x_range), ("y", &mut settings.y_range)] {
+        if let Some(value) = table.get(key) {
+            *range = number_pair(value).filter(|(start, end)| start < end);
+            if range.is_none() {
+                eprintln!(
+                    "Warning: view.{}: expected [start, end] with start lower than end but \
+                    found {}",
+                    key, value
+                );
+            }
+        }
+    }
+    settings
+}
+
+/// Reads a finite number, either an integer or a float
+fn number(value: &toml::Value) -> Option<f32> {
+    let number = match value {
+        toml::Value::Integer(number) => *number as f32,
+        toml::Value::Float(number) => *number as f32,
+        _ => return None,
+    };
+    number.is_finite().then_some(number)
+}
+
+/// Reads a positive number, either an integer or a float
+fn positive_number(value: &toml::Value) -> Option<f32> {
+    number(value).filter(|number| *number > 0.0f32)
+}
+
+/// Reads an array of two numbers
+fn number_pair(value: &toml::Value) -> Option<(f32, f32)> {
+    match value.as_array()?.as_slice() {
+        [first, second] => Some((number(first)?, number(second)?)),
+        _ => None,
+    }
+}
+
+/**
+Styles of the curves from the `[[curves]]` array, the N-th table applies to the N-th curve.
+Each table can set the `width` in pixels, the `style` as `"solid"`, `"dashed"` or `"dotted"` and
+a custom `dash = [dash_length, gap_length]` in pixels which takes precedence over the style.
+Invalid values are reported with a warning and replaced by the default ones.
+*/
+pub fn curve_styles(config: &toml::value::Table) -> Vec<LineStyle> {
+    let tables = match config.get("curves") {
+        None => return Vec::new(),
+        Some(toml::Value::Array(tables)) => tables,
+        Some(_) => {
+            eprintln!("Warning: curves: expected an array of tables");
+            return Vec::new();
+        }
+    };
+    let mut styles = Vec::new();
+    for (i, table) in tables.iter().enumerate() {
+        let mut style = LineStyle::default();
+        let table = match table {
+            toml::Value::Table(table) => table,
+            _ => {
+                eprintln!("Warning: curves[{}]: expected a table", i);
+                styles.push(style);
+                continue;
+            }
+        };
+        warn_unknown_keys(
+            table,
+            &format!("curves[{}].", i),
+            &["width", "style", "dash"],
+        );
+        if let Some(value) = table.get("width") {
+            match positive_number(value) {
+                Some(width) => style.width = width,
+                None => eprintln!(
+                    "Warning: curves[{}].width: expected a positive number but found {}",
+                    i, value
+                ),
+            }
+        }
+        if let Some(value) = table.get("style") {
+            match value.as_str().and_then(DashPattern::from_name) {
+                Some(pattern) => style.pattern = pattern,
+                None => eprintln!(
+                    "Warning: curves[{}].style: expected \"solid\", \"dashed\" or \"dotted\" \
+                    but found {}",
+                    i, value
+                ),
+            }
+        }
+        if let Some(value) = table.get("dash") {
+            let lengths = number_pair(value).filter(|(dash, gap)| *dash > 0.0f32 && *gap > 0.0f32);
+            match lengths {
+                Some((dash, gap)) => style.pattern = DashPattern::Custom { dash, gap },
+                None => eprintln!(
+                    "Warning: curves[{}].dash: expected two positive lengths but found {}",
+                    i, value
+                ),
+            }
+        }
+        styles.push(style);
+    }
+    styles
+}
+
+/**
+Page of the PDF exports from the `[pdf]` table, where `page` is a size accepted by
+`PageSetup::parse_size` and `margin` is the width of the margins in millimeters.
+Invalid values are reported with a warning and replaced by the default ones.
+*/
+pub fn pdf_page_setup(config: &toml::value::Table) -> PageSetup {
+    let mut page = PageSetup::default();
+    let table = match table(config, "pdf") {
+        Some(table) => table,
+        None => return page,
+    };
+    warn_unknown_keys(table, "pdf.", &["page", "margin"]);
+    if let Some(value) = table.get("page") {
+        match value.as_str().map(PageSetup::parse_size) {
+            Some(Ok(size)) => page.size = size,
+            Some(Err(e)) => eprintln!("Warning: pdf.page: {} but found {}", e, value),
+            None => eprintln!("Warning: pdf.page: expected a string but found {}", value),
+        }
+    }
+    if let Some(value) = table.get("margin") {
+        let margin = match value {
+            toml::Value::Integer(margin) => Some(*margin as f64),
+            toml::Value::Float(margin) => Some(*margin),
+            _ => None,
+        };
+        match margin.filter(|margin| *margin >= 0.0f64) {
+            Some(margin) => page.margin = PageSetup::margin_from_millimeters(margin),
+            None => eprintln!(
+                "Warning: pdf.margin: expected a non negative number of millimeters but found {}",
+                value
+            ),
+        }
+    }
+    page
+}
@@ -0,0 +1,120 @@
+use std::path::Path;
+
+/**
+DataSeries is a set of points read from a text file, plotted as the function that joins them with
+straight lines in order of x and that is not defined outside of them.
+Each line of the file holds the x and y coordinates of a point separated by spaces, tabs, commas or
+semicolons, further columns are ignored. Empty lines and lines starting with `#` are skipped, as
+is the first line when none of its columns is a number, so that files with a header can be used as
+they are.
+*/
+pub struct DataSeries {
+    points: Vec<(f32, f32)>,
+}
+
+impl DataSeries {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut points = Vec::new();
+        let mut first_line = true;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let columns = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .filter(|column| !column.is_empty())
+                .map(|column| column.parse::<f32>())
+                .collect::<Vec<_>>();
+            match columns.as_slice() {
+                [Ok(x), Ok(y), ..] if x.is_finite() => points.push((*x, *y)),
+                _ if first_line && columns.iter().all(Result::is_err) => {}
+                _ => {
+                    return Err(format!(
+                        "line {}: expected the x and y coordinates of a point",
+                        i + 1
+                    ))
+                }
+            }
+            first_line = false;
+        }
+        if points.len() < 2 {
+            return Err("expected at least two points".to_string());
+        }
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Ok(DataSeries { points })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| DataSeries::parse(&text))
+            .map_err(|e| format!("data file {}: {}", path.display(), e))
+    }
+
+    /// Interpolates the points around x, the result is NaN outside of the points
+    pub fn eval(&self, x: f32) -> f32 {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        if !(first.0..=last.0).contains(&x) {
+            return f32::NAN;
+        }
+        let next = self.points.partition_point(|point| point.0 <= x);
+        if next == self.points.len() {
+            return last.1;
+        }
+        let (start, end) = (self.points[next - 1], self.points[next]);
+        start.1 + (end.1 - start.1) * (x - start.0) / (end.0 - start.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_the_points_in_order_of_x() {
+        let series = DataSeries::parse("# comment\n2 4\n\n0,0,extra\n1;\t1\n").unwrap();
+        assert_eq!(
+            series.points,
+            [(0.0f32, 0.0f32), (1.0f32, 1.0f32), (2.0f32, 4.0f32)]
+        );
+    }
+
+    #[test]
+    fn parse_skips_a_header_made_of_names() {
+        let series = DataSeries::parse("time, value\n0, 1\n1, 2\n").unwrap();
+        assert_eq!(series.points, [(0.0f32, 1.0f32), (1.0f32, 2.0f32)]);
+    }
+
+    #[test]
+    fn parse_reports_the_lines_that_are_not_points() {
+        assert_eq!(
+            DataSeries::parse("1,abc\n2,3\n3,4\n").err(),
+            Some("line 1: expected the x and y coordinates of a point".to_string())
+        );
+        assert_eq!(
+            DataSeries::parse("x y\n0 0\n1\n").err(),
+            Some("line 3: expected the x and y coordinates of a point".to_string())
+        );
+        assert_eq!(
+            DataSeries::parse("0 0\nx y\n1 1\n").err(),
+            Some("line 2: expected the x and y coordinates of a point".to_string())
+        );
+        assert_eq!(
+            DataSeries::parse("x y\n0 0\n").err(),
+            Some("expected at least two points".to_string())
+        );
+    }
+
+    #[test]
+    fn eval_interpolates_between_the_points() {
+        let series = DataSeries::parse("0 0\n1 2\n3 -2\n").unwrap();
+        assert_eq!(series.eval(0.0f32), 0.0f32);
+        assert_eq!(series.eval(0.25f32), 0.5f32);
+        assert_eq!(series.eval(1.0f32), 2.0f32);
+        assert_eq!(series.eval(2.0f32), 0.0f32);
+        assert_eq!(series.eval(3.0f32), -2.0f32);
+        assert!(series.eval(-0.1f32).is_nan());
+        assert!(series.eval(3.1f32).is_nan());
+    }
+}
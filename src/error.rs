@@ -0,0 +1,55 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors that prevent a plot from being shown or saved
+#[derive(Debug)]
+pub enum FPlotError {
+    /// The Vulkan library could not be loaded
+    Loader(String),
+    /// The Vulkan instance could not be created
+    Instance(String),
+    /// No device meets the requirements of the renderer or the selected one cannot be created
+    DeviceSelection(String),
+    /// The window cannot be drawn to
+    Surface(String),
+    /// The swapchain of the window cannot be created
+    Swapchain(String),
+    /// A shader cannot be read or turned into a shader module
+    ShaderLoading { path: PathBuf, reason: String },
+    /// The memory of a buffer or of an image cannot be allocated
+    Allocation(String),
+    /// A Vulkan object cannot be created or a command fails, like when the device is lost
+    Device(String),
+    /// The renderer does not draw to the kind of target that the operation needs
+    Target(String),
+    /// The window cannot be created
+    Window(String),
+    /// The plot cannot be drawn, like when an expression cannot be parsed
+    InvalidPlot(String),
+    /// The plot cannot be saved to a file
+    Export(String),
+}
+
+impl fmt::Display for FPlotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FPlotError::Loader(reason) => write!(f, "cannot load the Vulkan library: {}", reason),
+            FPlotError::Instance(reason) => {
+                write!(f, "cannot create the Vulkan instance: {}", reason)
+            }
+            FPlotError::DeviceSelection(reason) => write!(f, "no usable device: {}", reason),
+            FPlotError::Surface(reason) => write!(f, "cannot draw to the window: {}", reason),
+            FPlotError::Swapchain(reason) => write!(f, "cannot create the swapchain: {}", reason),
+            FPlotError::ShaderLoading { path, reason } => {
+                write!(f, "cannot load the shader {}: {}", path.display(), reason)
+            }
+            FPlotError::Allocation(reason) => write!(f, "cannot allocate memory: {}", reason),
+            FPlotError::Device(reason) => write!(f, "Vulkan device error: {}", reason),
+            FPlotError::Target(reason) => write!(f, "wrong render target: {}", reason),
+            FPlotError::Window(reason) => write!(f, "cannot create the window: {}", reason),
+            FPlotError::InvalidPlot(reason) | FPlotError::Export(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for FPlotError {}
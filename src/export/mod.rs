@@ -0,0 +1,165 @@
+pub mod pdf;
+pub mod raster;
+pub mod svg;
+
+use crate::grid;
+use crate::renderer::style::LineStyle;
+use crate::theme::{Color, Theme};
+use crate::view::View;
+use nalgebra::*;
+use std::path::PathBuf;
+
+pub use crate::grid::{LabelAnchor, PageLabel, LABEL_FONT_SIZE};
+
+// Curve points are clamped to this many page sizes away from the page, so that poles do not
+// produce coordinates too big for the output formats
+const MAX_PAGE_DISTANCE: f64 = 10.0f64;
+
+/// Curve of a figure with the appearance it has on screen
+pub struct FigureCurve<'a> {
+    pub function: Box<dyn Fn(f32) -> f32 + 'a>,
+    pub color: Color,
+    pub style: LineStyle,
+}
+
+/**
+Figure describes a plot like the renderer draws it, so that the exporters can reproduce it
+without Vulkan. The size is in page units, which are the pixels of the window for the images and
+the units of the vector formats. Only the visible curves are part of the figure.
+*/
+pub struct Figure<'a> {
+    pub view: View,
+    pub size: Vector2<f64>,
+    pub theme: &'a Theme,
+    pub grid: bool,
+    pub curves: Vec<FigureCurve<'a>>,
+}
+
+/// Polyline in page coordinates, with the origin at the top left corner of the page
+pub struct PageLine {
+    pub points: Vec<Vector2<f64>>,
+    pub color: Color,
+    pub style: LineStyle,
+}
+
+impl<'a> Figure<'a> {
+    fn to_page(&self, point: Vector2<f32>) -> Vector2<f64> {
+        let half_extent = self.view.half_extent(self.size).cast::<f64>();
+        let normalized = (point - self.view.center)
+            .cast::<f64>()
+            .component_div(&half_extent);
+        Vector2::new(
+            (normalized.x + 1.0f64) * self.size.x / 2.0f64,
+            (1.0f64 - normalized.y) * self.size.y / 2.0f64,
+        )
+    }
+
+    /// Visible world rectangle as its bottom left and top right corners
+    fn world_bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        let half_extent = self.view.half_extent(self.size);
+        (
+            self.view.center - half_extent,
+            self.view.center + half_extent,
+        )
+    }
+
+    /// The lines of the figure in drawing order: the grid, the axes and the curves. The curves are
+    /// sampled once per page unit, like the renderer does per pixel, and are split where they
+    /// are not defined
+    pub fn lines(&self) -> Vec<PageLine> {
+        let (min, max) = self.world_bounds();
+        let mut lines = Vec::new();
+        if self.grid {
+            for x in grid::lines(min.x, max.x, self.size.x as f32) {
+                lines.push(self.straight_line(
+                    Vector2::new(x, min.y),
+                    Vector2::new(x, max.y),
+                    self.theme.grid,
+                    LineStyle::GRID,
+                ));
+            }
+            for y in grid::lines(min.y, max.y, self.size.y as f32) {
+                lines.push(self.straight_line(
+                    Vector2::new(min.x, y),
+                    Vector2::new(max.x, y),
+                    self.theme.grid,
+                    LineStyle::GRID,
+                ));
+            }
+        }
+        lines.push(self.straight_line(
+            Vector2::new(min.x, 0.0f32),
+            Vector2::new(max.x, 0.0f32),
+            self.theme.axis,
+            LineStyle::AXIS,
+        ));
+        lines.push(self.straight_line(
+            Vector2::new(0.0f32, min.y),
+            Vector2::new(0.0f32, max.y),
+            self.theme.axis,
+            LineStyle::AXIS,
+        ));
+
+        let samples = self.size.x.ceil().max(2.0f64) as usize;
+        let step = (max.x - min.x) / (samples - 1) as f32;
+        for curve in &self.curves {
+            let mut points = Vec::new();
+            for i in 0..samples {
+                let x = min.x + i as f32 * step;
+                let y = (curve.function)(x);
+                if y.is_finite() {
+                    let mut point = self.to_page(Vector2::new(x, y));
+                    point.y = point.y.clamp(
+                        -MAX_PAGE_DISTANCE * self.size.y,
+                        (MAX_PAGE_DISTANCE + 1.0f64) * self.size.y,
+                    );
+                    points.push(point);
+                } else if !points.is_empty() {
+                    lines.push(PageLine {
+                        points: std::mem::take(&mut points),
+                        color: curve.color,
+                        style: curve.style,
+                    });
+                }
+            }
+            if !points.is_empty() {
+                lines.push(PageLine {
+                    points,
+                    color: curve.color,
+                    style: curve.style,
+                });
+            }
+        }
+        lines
+    }
+
+    fn straight_line(
+        &self,
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        color: Color,
+        style: LineStyle,
+    ) -> PageLine {
+        PageLine {
+            points: vec![self.to_page(start), self.to_page(end)],
+            color,
+            style,
+        }
+    }
+
+    /// Labels of the grid lines, like the renderer draws them
+    pub fn labels(&self) -> Vec<PageLabel> {
+        let (min, max) = self.world_bounds();
+        grid::labels(min, max, self.size)
+    }
+}
+
+/// Name of a file in the current directory that includes the current date and time, like
+/// `fplot-20220314-152653.svg`
+pub fn timestamped_path(extension: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "fplot-{}.{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ))
+}
@@ -0,0 +1,273 @@
+use super::{Figure, LABEL_FONT_SIZE};
+use crate::font::{self, Glyph, GLYPHS, GLYPH_ADVANCE, GLYPH_CAP_HEIGHT, GLYPH_STROKE_WIDTH};
+use crate::renderer::style::DashPattern;
+use crate::theme::Color;
+use nalgebra::*;
+use std::fmt::Write;
+use std::path::Path;
+
+const POINTS_PER_MILLIMETER: f64 = 72.0f64 / 25.4f64;
+
+// Named page sizes in millimeters, in portrait orientation
+const PAGE_SIZES: [(&str, f64, f64); 5] = [
+    ("a3", 297.0f64, 420.0f64),
+    ("a4", 210.0f64, 297.0f64),
+    ("a5", 148.0f64, 210.0f64),
+    ("letter", 215.9f64, 279.4f64),
+    ("legal", 215.9f64, 355.6f64),
+];
+
+/// Size of the page and of its margins, in points
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PageSetup {
+    pub size: Vector2<f64>,
+    pub margin: f64,
+}
+
+impl Default for PageSetup {
+    /// A4 in landscape orientation with margins of 15mm
+    fn default() -> Self {
+        PageSetup {
+            size: Vector2::new(297.0f64, 210.0f64) * POINTS_PER_MILLIMETER,
+            margin: 15.0f64 * POINTS_PER_MILLIMETER,
+        }
+    }
+}
+
+impl PageSetup {
+    /**
+    Parses a page size, either the name of a standard one (a3, a4, a5, letter and legal) with an
+    optional `-landscape` suffix or a custom size in millimeters like `180x120`.
+    */
+    pub fn parse_size(text: &str) -> Result<Vector2<f64>, String> {
+        let (name, landscape) = match text.strip_suffix("-landscape") {
+            Some(name) => (name, true),
+            None => (text, false),
+        };
+        let size = if let Some((_, width, height)) = PAGE_SIZES
+            .iter()
+            .find(|(size_name, _, _)| *size_name == name)
+        {
+            Vector2::new(*width, *height)
+        } else {
+            let invalid = || {
+                let names = PAGE_SIZES.iter().map(|(name, _, _)| *name);
+                format!(
+                    "expected one of {} or WIDTHxHEIGHT in millimeters",
+                    names.collect::<Vec<_>>().join(", ")
+                )
+            };
+            let (width, height) = name.split_once('x').ok_or_else(invalid)?;
+            let parse = |side: &str| {
+                side.trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|side| side.is_finite() && *side > 0.0f64)
+                    .ok_or_else(invalid)
+            };
+            Vector2::new(parse(width)?, parse(height)?)
+        };
+        let size = if landscape {
+            Vector2::new(size.y, size.x)
+        } else {
+            size
+        };
+        Ok(size * POINTS_PER_MILLIMETER)
+    }
+
+    /// Converts a margin in millimeters to points
+    pub fn margin_from_millimeters(margin: f64) -> f64 {
+        margin * POINTS_PER_MILLIMETER
+    }
+
+    /// Size of the area inside of the margins, where the plot is drawn
+    pub fn plot_size(&self) -> Vector2<f64> {
+        (self.size - Vector2::repeat(2.0f64 * self.margin)).map(|side| side.max(1.0f64))
+    }
+}
+
+/**
+Builds a single page PDF document of the figure, which is drawn inside of the margins of the page
+and should have the size of that area. The tick labels use the small stroked font the renderer
+draws them with, embedded in the document as a Type 3 font, so the file does not depend on the
+fonts installed where it is opened.
+*/
+pub fn to_pdf(figure: &Figure, page: &PageSetup) -> Vec<u8> {
+    let content = content_stream(figure, page);
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+            /Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R >>",
+            page.size.x, page.size.y
+        ),
+        stream_object(&content),
+    ];
+
+    // the font is the next object, it is followed by the dictionary of its glyph procedures and
+    // then by the procedures themselves. The identifiers of the objects start from 1
+    let char_procs_id = objects.len() + 2;
+    let first_glyph_id = char_procs_id + 1;
+    let first_char = GLYPHS.iter().map(|glyph| glyph.code).min().unwrap();
+    let last_char = GLYPHS.iter().map(|glyph| glyph.code).max().unwrap();
+    let widths = (first_char..=last_char)
+        .map(|code| match GLYPHS.iter().any(|glyph| glyph.code == code) {
+            true => GLYPH_ADVANCE.to_string(),
+            false => "0".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let differences = GLYPHS
+        .iter()
+        .map(|glyph| format!("{} /{}", glyph.code, glyph.name))
+        .collect::<Vec<_>>()
+        .join(" ");
+    objects.push(format!(
+        "<< /Type /Font /Subtype /Type3 /FontBBox [0 {} {} {}] \
+        /FontMatrix [0.001 0 0 0.001 0 0] /CharProcs {} 0 R \
+        /Encoding << /Type /Encoding /Differences [{}] >> \
+        /FirstChar {} /LastChar {} /Widths [{}] /Resources << >> >>",
+        -GLYPH_STROKE_WIDTH,
+        GLYPH_ADVANCE,
+        GLYPH_CAP_HEIGHT + GLYPH_STROKE_WIDTH,
+        char_procs_id,
+        differences,
+        first_char,
+        last_char,
+        widths
+    ));
+    let char_procs = GLYPHS
+        .iter()
+        .enumerate()
+        .map(|(i, glyph)| format!("/{} {} 0 R", glyph.name, first_glyph_id + i))
+        .collect::<Vec<_>>()
+        .join(" ");
+    objects.push(format!("<< {} >>", char_procs));
+    for glyph in GLYPHS.iter() {
+        objects.push(stream_object(&glyph_procedure(glyph)));
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref_offset = pdf.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        trailer,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    );
+    pdf.extend_from_slice(trailer.as_bytes());
+    pdf
+}
+
+pub fn write_pdf(path: &Path, figure: &Figure, page: &PageSetup) -> Result<(), String> {
+    std::fs::write(path, to_pdf(figure, page))
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+fn stream_object(content: &str) -> String {
+    format!(
+        "<< /Length {} >>\nstream\n{}\nendstream",
+        content.len(),
+        content
+    )
+}
+
+fn color_components(color: Color) -> String {
+    format!("{:.3} {:.3} {:.3}", color[0], color[1], color[2])
+}
+
+/// Draws the figure in the page coordinates it uses, which have the origin at the top left corner
+/// of the plot area and the y axis pointing down
+fn content_stream(figure: &Figure, page: &PageSetup) -> String {
+    let mut content = String::new();
+    // writing to a String cannot fail
+    let _ = writeln!(
+        content,
+        "1 0 0 -1 {:.2} {:.2} cm",
+        page.margin,
+        page.size.y - page.margin
+    );
+    let _ = writeln!(
+        content,
+        "{} rg 0 0 {:.2} {:.2} re f",
+        color_components(figure.theme.background),
+        figure.size.x,
+        figure.size.y
+    );
+
+    let _ = writeln!(content, "q");
+    let _ = writeln!(
+        content,
+        "0 0 {:.2} {:.2} re W n",
+        figure.size.x, figure.size.y
+    );
+    let _ = writeln!(content, "1 j 0 J");
+    for line in figure.lines() {
+        let dash_array = match line.style.pattern {
+            DashPattern::Solid => String::new(),
+            pattern => {
+                let (dash, gap) = pattern.lengths(line.style.width);
+                format!("{:.2} {:.2}", dash, gap)
+            }
+        };
+        let _ = writeln!(
+            content,
+            "{} RG {:.2} w [{}] 0 d",
+            color_components(line.color),
+            line.style.width,
+            dash_array
+        );
+        for (i, point) in line.points.iter().enumerate() {
+            let operator = if i == 0 { "m" } else { "l" };
+            let _ = writeln!(content, "{:.2} {:.2} {}", point.x, point.y, operator);
+        }
+        let _ = writeln!(content, "S");
+    }
+    let _ = writeln!(content, "Q");
+
+    // the glyphs are stroked with the stroke color
+    let axis_color = color_components(figure.theme.axis);
+    let _ = writeln!(content, "{} RG {} rg [] 0 d", axis_color, axis_color);
+    for label in figure.labels() {
+        let origin = label.origin();
+        // the text matrix flips the y axis back, otherwise the glyphs would be upside down
+        let _ = writeln!(
+            content,
+            "BT /F1 {:.2} Tf 1 0 0 -1 {:.2} {:.2} Tm ({}) Tj ET",
+            LABEL_FONT_SIZE, origin.x, origin.y, label.text
+        );
+    }
+    content
+}
+
+/// Content of the procedure that draws a glyph, which uses the stroke color of the text
+fn glyph_procedure(glyph: &Glyph) -> String {
+    let mut procedure = format!(
+        "{} 0 0 {} {} {} d1\n{} w 1 J 1 j\n",
+        GLYPH_ADVANCE,
+        -GLYPH_STROKE_WIDTH,
+        GLYPH_ADVANCE,
+        GLYPH_CAP_HEIGHT + GLYPH_STROKE_WIDTH,
+        GLYPH_STROKE_WIDTH
+    );
+    for stroke in glyph.strokes {
+        for (i, point) in stroke.iter().enumerate() {
+            let (x, y) = font::glyph_point(*point);
+            let operator = if i == 0 { "m" } else { "l" };
+            let _ = writeln!(procedure, "{:.0} {:.0} {}", x, y, operator);
+        }
+        procedure.push_str("S\n");
+    }
+    procedure
+}
@@ -0,0 +1,66 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Writes an image given as rows of RGBA bytes, from the top one to the bottom one, to a PNG file
+pub fn write_png(path: &Path, width: u32, height: u32, rgba: &[u8]) -> Result<(), String> {
+    let file =
+        File::create(path).map_err(|e| format!("cannot create {}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+/// Animated GIF that loops forever, written one frame at a time
+pub struct GifWriter {
+    encoder: gif::Encoder<BufWriter<File>>,
+    width: u16,
+    height: u16,
+    // Time each frame is shown for, in hundredths of a second
+    delay: u16,
+}
+
+impl GifWriter {
+    pub fn create(
+        path: &Path,
+        width: u32,
+        height: u32,
+        frame_duration: f32,
+    ) -> Result<Self, String> {
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(format!("GIF images are at most {0}x{0} pixels", u16::MAX)),
+        };
+        let file =
+            File::create(path).map_err(|e| format!("cannot create {}: {}", path.display(), e))?;
+        let encoder = gif::Encoder::new(BufWriter::new(file), width, height, &[])
+            .and_then(|mut encoder| {
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                Ok(encoder)
+            })
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        Ok(GifWriter {
+            encoder,
+            width,
+            height,
+            delay: (frame_duration * 100.0f32)
+                .round()
+                .clamp(1.0f32, u16::MAX as f32) as u16,
+        })
+    }
+
+    /// Appends a frame given as rows of RGBA bytes, its colors are reduced to the 256 of the
+    /// palette of the frame
+    pub fn write_frame(&mut self, rgba: &mut [u8]) -> Result<(), String> {
+        // 10 is the speed suggested by the encoder for a good quality in a reasonable time
+        let mut frame = gif::Frame::from_rgba_speed(self.width, self.height, rgba, 10);
+        frame.delay = self.delay;
+        self.encoder
+            .write_frame(&frame)
+            .map_err(|e| format!("cannot write a GIF frame: {}", e))
+    }
+}
@@ -0,0 +1,85 @@
+use super::{Figure, LABEL_FONT_SIZE};
+use crate::font;
+use crate::renderer::style::DashPattern;
+use crate::theme::color_to_hex;
+use nalgebra::*;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Builds a standalone SVG document of the figure, one SVG unit for each page unit
+pub fn to_svg(figure: &Figure) -> String {
+    let (width, height) = (figure.size.x, figure.size.y);
+    let mut svg = String::new();
+    // writing to a String cannot fail
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<defs><clipPath id="plot-area"><rect x="0" y="0" width="{}" height="{}"/></clipPath></defs>"#,
+        width, height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="0" y="0" width="{}" height="{}" fill="{}"/>"#,
+        width,
+        height,
+        color_to_hex(figure.theme.background)
+    );
+
+    let _ = writeln!(
+        svg,
+        r#"<g clip-path="url(#plot-area)" fill="none" stroke-linejoin="round">"#
+    );
+    for line in figure.lines() {
+        let dash_array = match line.style.pattern {
+            DashPattern::Solid => String::new(),
+            pattern => {
+                let (dash, gap) = pattern.lengths(line.style.width);
+                format!(r#" stroke-dasharray="{} {}""#, dash, gap)
+            }
+        };
+        let _ = writeln!(
+            svg,
+            r#"<polyline points="{}" stroke="{}" stroke-width="{}"{}/>"#,
+            points_attribute(&line.points),
+            color_to_hex(line.color),
+            line.style.width,
+            dash_array
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    // the labels are stroked with the font of the renderer, so they look like on screen
+    let _ = writeln!(
+        svg,
+        r#"<g fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round">"#,
+        color_to_hex(figure.theme.axis),
+        font::stroke_width(LABEL_FONT_SIZE)
+    );
+    for label in figure.labels() {
+        for stroke in label.strokes() {
+            let _ = writeln!(svg, r#"<polyline points="{}"/>"#, points_attribute(&stroke));
+        }
+    }
+    let _ = writeln!(svg, "</g>");
+    let _ = writeln!(svg, "</svg>");
+    svg
+}
+
+/// Points of a polyline as the value of its `points` attribute
+fn points_attribute(points: &[Vector2<f64>]) -> String {
+    points
+        .iter()
+        .map(|point| format!("{:.2},{:.2}", point.x, point.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn write_svg(path: &Path, figure: &Figure) -> Result<(), String> {
+    std::fs::write(path, to_svg(figure))
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
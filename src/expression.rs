@@ -0,0 +1,20 @@
+/// Function of x and of the time t parsed from its textual form, like `sin(x - t) * x`
+pub struct Expression {
+    function: Box<dyn Fn(f64, f64) -> f64>,
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let function = text
+            .parse::<meval::Expr>()
+            .and_then(|expr| expr.bind2("x", "t"))
+            .map_err(|e| format!("invalid expression \"{}\": {}", text, e))?;
+        Ok(Expression {
+            function: Box::new(function),
+        })
+    }
+
+    pub fn eval_at(&self, x: f32, t: f32) -> f32 {
+        (self.function)(x as f64, t as f64) as f32
+    }
+}
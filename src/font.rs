@@ -0,0 +1,234 @@
+use nalgebra::*;
+
+// Metrics of the font in glyph units, 1000 of them make the font size
+pub const GLYPH_ADVANCE: i32 = 600;
+pub const GLYPH_CAP_HEIGHT: i32 = 700;
+pub const GLYPH_STROKE_WIDTH: i32 = 80;
+// Size of the cells of the grid the glyphs are drawn on, in glyph units
+const CELL_WIDTH: f32 = 100.0f32;
+const CELL_HEIGHT: f32 = GLYPH_CAP_HEIGHT as f32 / 6.0f32;
+
+/// Glyph of the font of the tick labels, drawn as polylines on a grid 4 cells wide and 6 cells
+/// tall. The font has only the characters of the numbers
+pub struct Glyph {
+    pub code: u8,
+    pub name: &'static str,
+    pub strokes: &'static [&'static [(f32, f32)]],
+}
+
+pub const GLYPHS: [Glyph; 14] = [
+    Glyph {
+        code: b'+',
+        name: "plus",
+        strokes: &[&[(0.5, 3.0), (3.5, 3.0)], &[(2.0, 1.5), (2.0, 4.5)]],
+    },
+    Glyph {
+        code: b'-',
+        name: "minus",
+        strokes: &[&[(0.5, 3.0), (3.5, 3.0)]],
+    },
+    Glyph {
+        code: b'.',
+        name: "period",
+        strokes: &[&[(1.8, 0.0), (2.2, 0.0), (2.2, 0.4), (1.8, 0.4), (1.8, 0.0)]],
+    },
+    Glyph {
+        code: b'0',
+        name: "zero",
+        strokes: &[&[
+            (1.0, 0.0),
+            (3.0, 0.0),
+            (4.0, 1.0),
+            (4.0, 5.0),
+            (3.0, 6.0),
+            (1.0, 6.0),
+            (0.0, 5.0),
+            (0.0, 1.0),
+            (1.0, 0.0),
+        ]],
+    },
+    Glyph {
+        code: b'1',
+        name: "one",
+        strokes: &[
+            &[(1.0, 5.0), (2.0, 6.0), (2.0, 0.0)],
+            &[(1.0, 0.0), (3.0, 0.0)],
+        ],
+    },
+    Glyph {
+        code: b'2',
+        name: "two",
+        strokes: &[&[
+            (0.0, 5.0),
+            (1.0, 6.0),
+            (3.0, 6.0),
+            (4.0, 5.0),
+            (4.0, 4.0),
+            (0.0, 0.0),
+            (4.0, 0.0),
+        ]],
+    },
+    Glyph {
+        code: b'3',
+        name: "three",
+        strokes: &[
+            &[
+                (0.0, 5.0),
+                (1.0, 6.0),
+                (3.0, 6.0),
+                (4.0, 5.0),
+                (4.0, 4.0),
+                (3.0, 3.0),
+                (1.0, 3.0),
+            ],
+            &[
+                (3.0, 3.0),
+                (4.0, 2.0),
+                (4.0, 1.0),
+                (3.0, 0.0),
+                (1.0, 0.0),
+                (0.0, 1.0),
+            ],
+        ],
+    },
+    Glyph {
+        code: b'4',
+        name: "four",
+        strokes: &[&[(3.0, 0.0), (3.0, 6.0), (0.0, 2.0), (4.0, 2.0)]],
+    },
+    Glyph {
+        code: b'5',
+        name: "five",
+        strokes: &[&[
+            (4.0, 6.0),
+            (0.0, 6.0),
+            (0.0, 3.0),
+            (3.0, 3.0),
+            (4.0, 2.0),
+            (4.0, 1.0),
+            (3.0, 0.0),
+            (0.0, 0.0),
+        ]],
+    },
+    Glyph {
+        code: b'6',
+        name: "six",
+        strokes: &[&[
+            (4.0, 5.0),
+            (3.0, 6.0),
+            (1.0, 6.0),
+            (0.0, 5.0),
+            (0.0, 1.0),
+            (1.0, 0.0),
+            (3.0, 0.0),
+            (4.0, 1.0),
+            (4.0, 2.0),
+            (3.0, 3.0),
+            (0.0, 3.0),
+        ]],
+    },
+    Glyph {
+        code: b'7',
+        name: "seven",
+        strokes: &[&[(0.0, 6.0), (4.0, 6.0), (1.0, 0.0)]],
+    },
+    Glyph {
+        code: b'8',
+        name: "eight",
+        strokes: &[&[
+            (1.0, 3.0),
+            (0.0, 4.0),
+            (0.0, 5.0),
+            (1.0, 6.0),
+            (3.0, 6.0),
+            (4.0, 5.0),
+            (4.0, 4.0),
+            (3.0, 3.0),
+            (1.0, 3.0),
+            (0.0, 2.0),
+            (0.0, 1.0),
+            (1.0, 0.0),
+            (3.0, 0.0),
+            (4.0, 1.0),
+            (4.0, 2.0),
+            (3.0, 3.0),
+        ]],
+    },
+    Glyph {
+        code: b'9',
+        name: "nine",
+        strokes: &[&[
+            (4.0, 3.0),
+            (1.0, 3.0),
+            (0.0, 4.0),
+            (0.0, 5.0),
+            (1.0, 6.0),
+            (3.0, 6.0),
+            (4.0, 5.0),
+            (4.0, 1.0),
+            (3.0, 0.0),
+            (1.0, 0.0),
+            (0.0, 1.0),
+        ]],
+    },
+    Glyph {
+        code: b'e',
+        name: "e",
+        strokes: &[&[
+            (0.0, 2.0),
+            (4.0, 2.0),
+            (4.0, 3.0),
+            (3.0, 4.0),
+            (1.0, 4.0),
+            (0.0, 3.0),
+            (0.0, 1.0),
+            (1.0, 0.0),
+            (4.0, 0.0),
+        ]],
+    },
+];
+
+/// Position in glyph units of a point of the grid of the glyphs, the grid is centered in the
+/// advance and spans the cap height. The y axis points up from the baseline
+pub fn glyph_point((x, y): (f32, f32)) -> (f32, f32) {
+    let left = (GLYPH_ADVANCE as f32 - 4.0f32 * CELL_WIDTH) / 2.0f32;
+    (left + x * CELL_WIDTH, y * CELL_HEIGHT)
+}
+
+/// Width of a text with the given font size
+pub fn text_width(text: &str, size: f64) -> f64 {
+    text.chars().count() as f64 * size * GLYPH_ADVANCE as f64 / 1000.0f64
+}
+
+/// Height of the glyphs with the given font size
+pub fn cap_height(size: f64) -> f64 {
+    size * GLYPH_CAP_HEIGHT as f64 / 1000.0f64
+}
+
+/// Width of the strokes of the glyphs with the given font size
+pub fn stroke_width(size: f64) -> f64 {
+    size * GLYPH_STROKE_WIDTH as f64 / 1000.0f64
+}
+
+/// The polylines that draw a text with the given font size, in page coordinates with the y axis
+/// pointing down, starting from the left end of its baseline. Characters without a glyph are
+/// left blank
+pub fn text_strokes(text: &str, origin: Vector2<f64>, size: f64) -> Vec<Vec<Vector2<f64>>> {
+    let scale = size / 1000.0f64;
+    let mut strokes = Vec::new();
+    for (i, code) in text.chars().enumerate() {
+        let left = origin.x + i as f64 * GLYPH_ADVANCE as f64 * scale;
+        let glyph = match GLYPHS.iter().find(|glyph| glyph.code as char == code) {
+            Some(glyph) => glyph,
+            None => continue,
+        };
+        for stroke in glyph.strokes {
+            let points = stroke.iter().map(|point| {
+                let (x, y) = glyph_point(*point);
+                Vector2::new(left + x as f64 * scale, origin.y - y as f64 * scale)
+            });
+            strokes.push(points.collect());
+        }
+    }
+    strokes
+}
@@ -0,0 +1,134 @@
+use crate::font;
+use nalgebra::*;
+
+// Aimed distance in pixels between two grid lines and the limits on the lines of an axis
+const TARGET_SPACING_PIXELS: f32 = 100.0f32;
+const MIN_LINES: f32 = 2.0f32;
+pub const MAX_LINES: usize = 64;
+// Size of the tick labels and their distance from the axes, in page units
+pub const LABEL_FONT_SIZE: f64 = 12.0f64;
+const LABEL_OFFSET: f64 = 4.0f64;
+
+/// Where a label is placed relative to its position
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LabelAnchor {
+    /// The position is the middle of the top side of the text
+    Top,
+    /// The position is the middle of the left side of the text
+    Left,
+}
+
+/// Label of a grid line, with its position in page coordinates, which have the origin at the top
+/// left corner of the page
+pub struct PageLabel {
+    pub position: Vector2<f64>,
+    pub anchor: LabelAnchor,
+    pub text: String,
+}
+
+impl PageLabel {
+    /// The left end of the baseline of the text
+    pub fn origin(&self) -> Vector2<f64> {
+        let width = font::text_width(&self.text, LABEL_FONT_SIZE);
+        let cap_height = font::cap_height(LABEL_FONT_SIZE);
+        match self.anchor {
+            LabelAnchor::Top => self.position + Vector2::new(-width / 2.0f64, cap_height),
+            LabelAnchor::Left => self.position + Vector2::new(0.0f64, cap_height / 2.0f64),
+        }
+    }
+
+    /// The polylines that draw the text in page coordinates
+    pub fn strokes(&self) -> Vec<Vec<Vector2<f64>>> {
+        font::text_strokes(&self.text, self.origin(), LABEL_FONT_SIZE)
+    }
+}
+
+/// Distance between the grid lines for an axis that shows `range` world units over the given
+/// amount of pixels, rounded to 1, 2 or 5 times a power of 10
+pub fn step(range: f32, length_pixels: f32) -> f32 {
+    let target_lines = (length_pixels / TARGET_SPACING_PIXELS).max(MIN_LINES);
+    let raw_step = range / target_lines;
+    let magnitude = 10.0f32.powf(raw_step.log10().floor());
+    let normalized = raw_step / magnitude;
+    let multiplier = if normalized < 1.5f32 {
+        1.0f32
+    } else if normalized < 3.5f32 {
+        2.0f32
+    } else if normalized < 7.5f32 {
+        5.0f32
+    } else {
+        10.0f32
+    };
+    multiplier * magnitude
+}
+
+/// Positions of the grid lines inside of the given interval, at most `MAX_LINES` of them
+pub fn lines(start: f32, end: f32, length_pixels: f32) -> Vec<f32> {
+    let step = step(end - start, length_pixels);
+    if !step.is_normal() {
+        return Vec::new();
+    }
+    let first = (start / step).ceil() as i64;
+    (0..MAX_LINES as i64)
+        .map(|i| (first + i) as f32 * step)
+        .take_while(|line| *line <= end)
+        .collect()
+}
+
+/// Label of a grid line, with the decimals needed to tell apart lines at the given distance
+pub fn label(value: f32, step: f32) -> String {
+    let decimals = (-step.log10().floor()).max(0.0f32) as usize;
+    // avoids labels like -0.0 for lines at the origin that accumulated rounding errors
+    let value = if value.abs() < step / 2.0f32 {
+        0.0f32
+    } else {
+        value
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/**
+Labels of the grid lines of the world rectangle between the corners `min` and `max` when it is
+shown on a page of the given size. They are placed next to the axes, or next to the border of the
+page when an axis is not visible, and the origin is not labelled.
+*/
+pub fn labels(min: Vector2<f32>, max: Vector2<f32>, size: Vector2<f64>) -> Vec<PageLabel> {
+    let to_page = |point: Vector2<f32>| {
+        let normalized = (point - min)
+            .cast::<f64>()
+            .component_div(&(max - min).cast::<f64>());
+        Vector2::new(normalized.x * size.x, (1.0f64 - normalized.y) * size.y)
+    };
+    let origin = to_page(Vector2::new(0.0f32, 0.0f32));
+    let x_labels_y = origin
+        .y
+        .min(size.y - LABEL_FONT_SIZE - LABEL_OFFSET)
+        .max(0.0f64)
+        + LABEL_OFFSET;
+    let y_labels_x = origin.x.min(size.x - 4.0f64 * LABEL_FONT_SIZE).max(0.0f64) + LABEL_OFFSET;
+
+    let mut labels = Vec::new();
+    let x_step = step(max.x - min.x, size.x as f32);
+    for x in lines(min.x, max.x, size.x as f32) {
+        if (x / x_step).round() == 0.0f32 {
+            continue;
+        }
+        labels.push(PageLabel {
+            position: Vector2::new(to_page(Vector2::new(x, 0.0f32)).x, x_labels_y),
+            anchor: LabelAnchor::Top,
+            text: label(x, x_step),
+        });
+    }
+    let y_step = step(max.y - min.y, size.y as f32);
+    for y in lines(min.y, max.y, size.y as f32) {
+        if (y / y_step).round() == 0.0f32 {
+            continue;
+        }
+        labels.push(PageLabel {
+            position: Vector2::new(y_labels_x, to_page(Vector2::new(0.0f32, y)).y),
+            anchor: LabelAnchor::Left,
+            text: label(y, y_step),
+        });
+    }
+    labels
+}
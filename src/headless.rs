@@ -0,0 +1,41 @@
+use crate::cli::RenderArgs;
+use fplot::export::pdf::PageSetup;
+use fplot::Curve;
+
+/**
+Renders the plot described by the arguments without a window. The x interval is shown entirely and
+the y one is fitted to the curve over all the frames. The output is an SVG file or a PDF document,
+drawn without Vulkan, when its extension is `svg` or `pdf`, an animated GIF image when it is `gif`
+and PNG images otherwise.
+*/
+pub fn render(args: &RenderArgs, config: &toml::value::Table) -> Result<(), String> {
+    let curve = Curve::from_expression(&args.expression)?;
+    let mut plot = crate::configured_plot(vec![curve], config, None)?;
+    plot.x_range = Some(args.x_range);
+    plot.y_range = None;
+    plot.window.size = args.size;
+    plot.device = args.device.clone();
+    // the page settings of the arguments take precedence over the config ones
+    if let Some(size) = args.page {
+        plot.pdf_page.size = size;
+    }
+    if let Some(margin) = args.margin {
+        plot.pdf_page.margin = PageSetup::margin_from_millimeters(margin);
+    }
+
+    let extension = args
+        .output
+        .extension()
+        .and_then(|extension| extension.to_str());
+    let result = match extension {
+        Some("svg" | "pdf") if args.frames > 1 => {
+            return Err("animations can only be saved as GIF or PNG images".to_string())
+        }
+        Some("svg") => plot.save_svg(&args.output),
+        Some("pdf") => plot.save_pdf(&args.output),
+        Some("gif") => plot.save_animation(&args.output, args.frames, args.time_step),
+        _ if args.frames > 1 => plot.save_animation(&args.output, args.frames, args.time_step),
+        _ => plot.save_png(&args.output),
+    };
+    result.map_err(|e| e.to_string())
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use winit::event::VirtualKeyCode;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    ZoomIn,
+    ZoomOut,
+    Reset,
+    Fit,
+    ToggleEqualAspect,
+    ToggleGrid,
+    /// Switches to the next of the available themes
+    CycleTheme,
+    ViewBack,
+    ViewForward,
+    /// Shows or hides the curve with the given index, counting from 0
+    ToggleCurve(usize),
+    Screenshot,
+    /// Saves the current view of the plot as an SVG file
+    ExportSvg,
+    /// Saves the current view of the plot as a PDF file
+    ExportPdf,
+}
+
+const NAMED_ACTIONS: [(&str, Action); 16] = [
+    ("pan_up", Action::PanUp),
+    ("pan_down", Action::PanDown),
+    ("pan_left", Action::PanLeft),
+    ("pan_right", Action::PanRight),
+    ("zoom_in", Action::ZoomIn),
+    ("zoom_out", Action::ZoomOut),
+    ("reset", Action::Reset),
+    ("fit", Action::Fit),
+    ("toggle_equal_aspect", Action::ToggleEqualAspect),
+    ("toggle_grid", Action::ToggleGrid),
+    ("cycle_theme", Action::CycleTheme),
+    ("view_back", Action::ViewBack),
+    ("view_forward", Action::ViewForward),
+    ("screenshot", Action::Screenshot),
+    ("export_svg", Action::ExportSvg),
+    ("export_pdf", Action::ExportPdf),
+];
+
+const TOGGLE_CURVE_PREFIX: &str = "toggle_curve_";
+
+impl Action {
+    /// Parses the name used for the action in the config file, the curves are toggled with
+    /// `toggle_curve_N` where N starts from 1
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(index) = name.strip_prefix(TOGGLE_CURVE_PREFIX) {
+            return match index.parse::<usize>() {
+                Ok(index) if index >= 1 => Some(Action::ToggleCurve(index - 1)),
+                _ => None,
+            };
+        }
+        NAMED_ACTIONS
+            .iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|(_, action)| *action)
+    }
+}
+
+/// Map from the keys to the actions they trigger
+pub struct KeyBindings {
+    bindings: HashMap<VirtualKeyCode, Action>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut bindings = HashMap::from([
+            (VirtualKeyCode::Up, Action::PanUp),
+            (VirtualKeyCode::Down, Action::PanDown),
+            (VirtualKeyCode::Left, Action::PanLeft),
+            (VirtualKeyCode::Right, Action::PanRight),
+            (VirtualKeyCode::Equals, Action::ZoomIn),
+            (VirtualKeyCode::Plus, Action::ZoomIn),
+            (VirtualKeyCode::NumpadAdd, Action::ZoomIn),
+            (VirtualKeyCode::Minus, Action::ZoomOut),
+            (VirtualKeyCode::NumpadSubtract, Action::ZoomOut),
+            (VirtualKeyCode::R, Action::Reset),
+            (VirtualKeyCode::F, Action::Fit),
+            (VirtualKeyCode::E, Action::ToggleEqualAspect),
+            (VirtualKeyCode::G, Action::ToggleGrid),
+            (VirtualKeyCode::T, Action::CycleTheme),
+            (VirtualKeyCode::LBracket, Action::ViewBack),
+            (VirtualKeyCode::RBracket, Action::ViewForward),
+            (VirtualKeyCode::F12, Action::Screenshot),
+            (VirtualKeyCode::S, Action::ExportSvg),
+            (VirtualKeyCode::P, Action::ExportPdf),
+        ]);
+        let number_keys = [
+            VirtualKeyCode::Key1,
+            VirtualKeyCode::Key2,
+            VirtualKeyCode::Key3,
+            VirtualKeyCode::Key4,
+            VirtualKeyCode::Key5,
+            VirtualKeyCode::Key6,
+            VirtualKeyCode::Key7,
+            VirtualKeyCode::Key8,
+            VirtualKeyCode::Key9,
+        ];
+        for (i, key) in number_keys.into_iter().enumerate() {
+            bindings.insert(key, Action::ToggleCurve(i));
+        }
+        KeyBindings { bindings }
+    }
+}
+
+impl KeyBindings {
+    pub fn action(&self, key: VirtualKeyCode) -> Option<Action> {
+        self.bindings.get(&key).copied()
+    }
+
+    /**
+    Applies the bindings of a `[keybindings]` table on top of the current ones. Each entry maps an
+    action name to a key or to an array of keys, named like winit's `VirtualKeyCode` variants,
+    and replaces all the keys previously bound to that action, an empty array unbinds it.
+    Invalid entries are skipped and reported in the returned warnings.
+    */
+    pub fn apply_table(&mut self, table: &toml::value::Table) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (name, value) in table {
+            let action = match Action::from_name(name) {
+                Some(action) => action,
+                None => {
+                    warnings.push(format!("keybindings.{}: unknown action", name));
+                    continue;
+                }
+            };
+            let values = match value {
+                toml::Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+            // an empty array unbinds the action, otherwise at least a valid key is needed
+            let unbind = values.is_empty();
+            let mut keys = Vec::new();
+            for value in values {
+                match value.clone().try_into::<VirtualKeyCode>() {
+                    Ok(key) => keys.push(key),
+                    Err(_) => warnings.push(format!("keybindings.{}: unknown key {}", name, value)),
+                }
+            }
+            if keys.is_empty() && !unbind {
+                continue;
+            }
+
+            self.bindings
+                .retain(|_, bound_action| *bound_action != action);
+            for key in keys {
+                if let Some(previous) = self.bindings.insert(key, action) {
+                    warnings.push(format!(
+                        "keybindings.{}: key {:?} was bound to {:?}, the binding is replaced",
+                        name, key, previous
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
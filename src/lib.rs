@@ -0,0 +1,38 @@
+/*!
+FPlot plots functions of x with Vulkan, in an interactive window or offscreen, and exports the
+plots as PNG, SVG and PDF files.
+
+A `Plot` describes the curves and how they are shown, `run_window` opens a window for it. The
+failures of the renderer are returned as an `FPlotError` instead of aborting the program:
+```no_run
+use fplot::{Curve, Plot};
+
+let mut plot = Plot::default();
+plot.curves.push(Curve::from_expression("sin(x) * x").unwrap());
+plot.curves.push(Curve::new(f32::cos));
+plot.x_range = Some((-10.0f32, 10.0f32));
+fplot::run_window(plot)?;
+# Ok::<(), fplot::FPlotError>(())
+```
+*/
+
+pub mod config;
+pub mod data;
+mod error;
+pub mod export;
+pub mod expression;
+mod font;
+mod grid;
+pub mod keybindings;
+mod plot;
+pub mod renderer;
+pub mod theme;
+mod touch;
+pub mod view;
+mod window;
+
+pub use error::FPlotError;
+pub use plot::{Curve, Plot};
+pub use renderer::vk::graph_vk::GraphVk as Renderer;
+pub use view::View;
+pub use window::{run_window, WindowSettings};
@@ -1,112 +1,147 @@
-mod renderer;
+mod cli;
+mod headless;
 
-use crate::renderer::window_manager::WindowManager;
-use renderer::vk::graph_vk::GraphVk;
+use clap::Parser;
+use cli::{Cli, Command, ReportFormat, WindowArgs};
+use fplot::renderer::device::{self, DeviceSelector};
+use fplot::{config, theme, Curve, Plot, Renderer};
 
-use winit::dpi::PhysicalPosition;
-use winit::event::*;
-use winit::event_loop::ControlFlow;
-use winit::platform::run_return::EventLoopExtRunReturn;
+/// The curves of the expressions followed by the ones of the data series, or cos(x) when neither
+/// are given
+fn load_curves(args: &WindowArgs) -> Result<Vec<Curve>, String> {
+    let mut curves = Vec::new();
+    for text in &args.expressions {
+        curves.push(Curve::from_expression(text)?);
+    }
+    for path in &args.data_files {
+        curves.push(Curve::from_data_file(path)?);
+    }
+    if curves.is_empty() {
+        curves.push(Curve::new(f32::cos));
+    }
+    Ok(curves)
+}
 
-use nalgebra::*;
-use winit::event::MouseScrollDelta::LineDelta;
+/**
+Plot of the curves with the settings of the config, the styles of the curves, the themes starting
+from the selected one, the grid, the MSAA samples, the window, the key bindings and the PDF page.
+The `theme` given by the arguments is selected instead of the one of the config.
+*/
+fn configured_plot(
+    mut curves: Vec<Curve>,
+    config: &toml::value::Table,
+    theme: Option<&str>,
+) -> Result<Plot, String> {
+    for (curve, style) in curves.iter_mut().zip(config::curve_styles(config)) {
+        curve.style = style;
+    }
+    let mut themes = theme::available_themes();
+    let theme_index = config::select_theme(config, &mut themes, theme)?;
+    // the selected theme is shown first, cycling goes on in the same order
+    themes.rotate_left(theme_index);
 
-fn main() {
-    std::env::set_var("WINIT_UNIX_BACKEND", "x11");
-    let mut window = WindowManager::new((800u32, 800u32), None);
-    let mut gvk = GraphVk::new((800u32, 800u32), window.get_window_handle());
+    let mut plot = Plot::new();
+    plot.curves = curves;
+    plot.themes = themes;
+    plot.grid = config::grid_visible(config);
+    plot.msaa_samples = config::msaa_samples(config);
+    plot.window = config::window_settings(config);
+    plot.key_bindings = config::key_bindings(config);
+    plot.pdf_page = config::pdf_page_setup(config);
+    Ok(plot)
+}
+
+/// Prints the devices found, marking the one that would draw the plots, and why the others
+/// cannot be used
+fn list_devices(device: Option<&DeviceSelector>) -> Result<(), String> {
+    let devices = Renderer::list_devices().map_err(|e| e.to_string())?;
+    let env_device = device.is_none().then(DeviceSelector::from_env).flatten();
+    let selected = device::select_device(&devices, device.or(env_device.as_ref()));
+    for device in &devices {
+        let status = match &device.rejection {
+            Some(reason) => format!("rejected: {}", reason),
+            None if selected.as_ref() == Ok(&device.index) => "selected".to_string(),
+            None => "usable".to_string(),
+        };
+        println!(
+            "{}: {} ({}, vendor {:#06x}, device {:#06x}, {} MiB) - {}",
+            device.index,
+            device.name,
+            device.device_type.name(),
+            device.vendor_id,
+            device.device_id,
+            device.local_memory / (1024 * 1024),
+            status
+        );
+    }
+    if let Err(e) = selected {
+        eprintln!("Warning: {}", e);
+    }
+    Ok(())
+}
+
+/// Prints the report of the Vulkan implementation and of the devices in the given format
+fn vulkan_info(format: ReportFormat) -> Result<(), String> {
+    let info = Renderer::vulkan_info().map_err(|e| e.to_string())?;
+    match format {
+        ReportFormat::Text => print!("{}", info.to_text()),
+        ReportFormat::Json => println!("{}", info.to_json()),
+    }
+    Ok(())
+}
 
-    let mut center = Vector3::new(0.0f32, 0.0f32, 0.0f32);
-    let mut left_mouse_pressed = false;
-    let mut last_mouse_pressed_pos: Option<PhysicalPosition<f64>> = None;
-    let mut zoom = 1.0f32;
-    gvk.prepare();
-    window.event_loop.run_return(|event, _, control_flow| {
-        *control_flow = ControlFlow::Wait;
-        match event {
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => {
-                    *control_flow = ControlFlow::Exit;
-                }
-                WindowEvent::KeyboardInput {
-                    input:
-                        KeyboardInput {
-                            virtual_keycode: Some(vkc),
-                            state: ElementState::Pressed,
-                            ..
-                        },
-                    ..
-                } => match vkc {
-                    VirtualKeyCode::Up => {
-                        center.y += 0.01f32;
-                        window.window.request_redraw()
-                    }
-                    VirtualKeyCode::Down => {
-                        center.y -= 0.01f32;
-                        window.window.request_redraw()
-                    }
-                    VirtualKeyCode::Left => {
-                        center.x += 0.01f32;
-                        window.window.request_redraw()
-                    }
-                    VirtualKeyCode::Right => {
-                        center.x -= 0.01f32;
-                        window.window.request_redraw()
-                    }
-                    _ => (),
-                },
-                WindowEvent::MouseInput {
-                    state: pressed_state,
-                    button: MouseButton::Left,
-                    ..
-                } => {
-                    left_mouse_pressed = match pressed_state {
-                        ElementState::Pressed => true,
-                        ElementState::Released => {
-                            last_mouse_pressed_pos = None;
-                            false
-                        }
-                    }
-                }
-                WindowEvent::CursorMoved { position: pos, .. } => {
-                    if last_mouse_pressed_pos.is_some() && left_mouse_pressed {
-                        let ex_pos_vec = Vector2::new(
-                            last_mouse_pressed_pos.unwrap().x,
-                            last_mouse_pressed_pos.unwrap().y,
-                        );
-                        let pos_vec = Vector2::new(pos.x, pos.y);
-                        let window_size_vec = Vector2::new(
-                            window.window.inner_size().width as f64,
-                            window.window.inner_size().height as f64,
-                        );
+/// Reports an error that prevents FPlot from running and exits
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("Error: {}", message);
+    std::process::exit(1);
+}
 
-                        let delta_pos = ex_pos_vec - pos_vec;
-                        let delta_pos_normalized =
-                            delta_pos.component_div(&window_size_vec) / zoom as f64;
-                        center -= Vector3::new(
-                            delta_pos_normalized.x as f32,
-                            delta_pos_normalized.y as f32,
-                            0.0f32,
-                        );
-                        window.window.request_redraw();
-                    }
-                    last_mouse_pressed_pos = Some(pos);
-                }
-                WindowEvent::MouseWheel { delta: val, .. } => {
-                    if let LineDelta(_, y) = val {
-                        zoom += y * 0.1f32 * zoom;
-                        window.window.request_redraw();
-                    }
-                }
-                _ => (),
-            },
-            Event::RedrawRequested(_) => {
-                let range = 1.0f32 / zoom;
-                gvk.fill_graph_buffer(-center.x - range, -center.x + range, |x| f32::cos(x));
-                gvk.set_transform(&center, zoom);
-                gvk.present_loop(&window.window);
-            }
-            _ => (),
+fn main() {
+    // winit opens the windows with X11 unless the user chose another backend
+    if std::env::var_os("WINIT_UNIX_BACKEND").is_none() {
+        std::env::set_var("WINIT_UNIX_BACKEND", "x11");
+    }
+    let cli = Cli::parse();
+    if cli.list_devices {
+        if let Err(e) = list_devices(cli.window.device.as_ref()) {
+            exit_with_error(&e);
         }
-    });
+        return;
+    }
+    if let Some(format) = cli.vulkan_info {
+        if let Err(e) = vulkan_info(format) {
+            exit_with_error(&e);
+        }
+        return;
+    }
+    let config = config::load_config_table();
+    if let Some(Command::Render(args)) = cli.command {
+        if let Err(e) = headless::render(&args, &config) {
+            exit_with_error(&e);
+        }
+        return;
+    }
+
+    // the arguments take precedence over the config file
+    let args = cli.window;
+    let mut plot = load_curves(&args)
+        .and_then(|curves| configured_plot(curves, &config, args.theme.as_deref()))
+        .unwrap_or_else(|e| exit_with_error(&e));
+    if let Some(size) = args.size {
+        plot.window.size = size;
+    }
+    plot.window.fullscreen = args.fullscreen || (plot.window.fullscreen && !args.windowed);
+    if let Some(present_mode) = args.present_mode {
+        plot.window.present_mode = present_mode;
+    }
+    let view = config::view_settings(&config);
+    plot.x_range = args.x_range.or(view.x_range);
+    plot.y_range = args.y_range.or(view.y_range);
+    if let Some(msaa_samples) = args.msaa {
+        plot.msaa_samples = msaa_samples;
+    }
+    plot.device = args.device;
+    if let Err(e) = fplot::run_window(plot) {
+        exit_with_error(&e.to_string());
+    }
 }
@@ -0,0 +1,422 @@
+use crate::config::DEFAULT_MSAA_SAMPLES;
+use crate::data::DataSeries;
+use crate::error::FPlotError;
+use crate::export::pdf::{self, PageSetup};
+use crate::export::raster::{self, GifWriter};
+use crate::export::{svg, Figure, FigureCurve};
+use crate::expression::Expression;
+use crate::keybindings::KeyBindings;
+use crate::renderer::device::DeviceSelector;
+use crate::renderer::style::LineStyle;
+use crate::renderer::vk::graph_vk::GraphVk;
+use crate::theme::{self, Color, Theme};
+use crate::view::View;
+use crate::window::{self, WindowSettings};
+use nalgebra::*;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Function of x, and possibly of the time t, drawn as a curve, with the style of its line and
+/// the color that replaces the one it takes from the theme
+pub struct Curve {
+    function: Box<dyn Fn(f32, f32) -> f32>,
+    pub style: LineStyle,
+    pub color: Option<Color>,
+}
+
+impl Curve {
+    pub fn new<F: Fn(f32) -> f32 + 'static>(function: F) -> Self {
+        Curve::animated(move |x, _| function(x))
+    }
+
+    /// Curve of a function of x and of the time t
+    pub fn animated<F: Fn(f32, f32) -> f32 + 'static>(function: F) -> Self {
+        Curve {
+            function: Box::new(function),
+            style: LineStyle::default(),
+            color: None,
+        }
+    }
+
+    /// Curve of an expression of x and of the time t like `sin(x - t) * x`
+    pub fn from_expression(text: &str) -> Result<Self, String> {
+        let expression = Expression::parse(text)?;
+        Ok(Curve::animated(move |x, t| expression.eval_at(x, t)))
+    }
+
+    /// Curve that joins the points of a data file with straight lines
+    pub fn from_data_file(path: &Path) -> Result<Self, String> {
+        let series = DataSeries::load(path)?;
+        Ok(Curve::new(move |x| series.eval(x)))
+    }
+
+    /// The function of x that the curve is at the time t
+    pub fn at(&self, t: f32) -> impl Fn(f32) -> f32 + '_ {
+        move |x| (self.function)(x, t)
+    }
+
+    pub fn eval(&self, x: f32, t: f32) -> f32 {
+        (self.function)(x, t)
+    }
+}
+
+/**
+Plot describes what a window shows and how: the curves, the initial view and the appearance.
+The curves take the colors of the theme in order, the first of the themes is shown when the window
+opens and the other ones are reached by cycling through them.
+
+Plots can be declared with the builder methods and then shown in a window or saved to a file:
+```no_run
+use fplot::{theme, Plot};
+
+Plot::new()
+    .function("sin(x)")
+    .color(theme::RED)
+    .range(-5.0..5.0)
+    .grid(true)
+    .save_png("sin.png")?;
+# Ok::<(), fplot::FPlotError>(())
+```
+*/
+pub struct Plot {
+    pub curves: Vec<Curve>,
+    /// Initial interval of x, the one of the default view when missing
+    pub x_range: Option<(f32, f32)>,
+    /// Initial interval of y, fitted to the curves when missing
+    pub y_range: Option<(f32, f32)>,
+    pub themes: Vec<Theme>,
+    pub grid: bool,
+    /// Time t at which the curves are drawn, the animations start from it
+    pub time: f32,
+    /// Samples per pixel used for anti-aliasing, lowered to what the GPU supports
+    pub msaa_samples: u32,
+    /// Device that draws the plot, the one of the `FPLOT_DEVICE` environment variable or the best
+    /// ranked one when missing
+    pub device: Option<DeviceSelector>,
+    pub window: WindowSettings,
+    pub key_bindings: KeyBindings,
+    /// Page of the PDF exports
+    pub pdf_page: PageSetup,
+    // First error of the builder methods, reported when the plot is shown or saved
+    error: Option<String>,
+}
+
+impl Default for Plot {
+    fn default() -> Self {
+        Plot {
+            curves: Vec::new(),
+            x_range: None,
+            y_range: None,
+            themes: theme::builtin_themes(),
+            grid: true,
+            time: 0.0f32,
+            msaa_samples: DEFAULT_MSAA_SAMPLES,
+            device: None,
+            window: WindowSettings::default(),
+            key_bindings: KeyBindings::default(),
+            pdf_page: PageSetup::default(),
+            error: None,
+        }
+    }
+}
+
+impl Plot {
+    pub fn new() -> Self {
+        Plot::default()
+    }
+
+    fn fail(mut self, error: String) -> Self {
+        self.error.get_or_insert(error);
+        self
+    }
+
+    /// Adds the curve of an expression of x like `sin(x) * x`
+    pub fn function(self, expression: &str) -> Self {
+        match Curve::from_expression(expression) {
+            Ok(curve) => self.curve(curve),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Adds the curve that joins the points of a data file
+    pub fn data_file<P: AsRef<Path>>(self, path: P) -> Self {
+        match Curve::from_data_file(path.as_ref()) {
+            Ok(curve) => self.curve(curve),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    pub fn curve(mut self, curve: Curve) -> Self {
+        self.curves.push(curve);
+        self
+    }
+
+    /// Sets the color of the last curve added
+    pub fn color(mut self, color: Color) -> Self {
+        match self.curves.last_mut() {
+            Some(curve) => curve.color = Some(color),
+            None => return self.fail("a color is set before adding a curve".to_string()),
+        }
+        self
+    }
+
+    /// Sets the width and the dash pattern of the last curve added
+    pub fn style(mut self, style: LineStyle) -> Self {
+        match self.curves.last_mut() {
+            Some(curve) => curve.style = style,
+            None => return self.fail("a style is set before adding a curve".to_string()),
+        }
+        self
+    }
+
+    /// Sets the initial interval of x
+    pub fn range(mut self, x_range: Range<f32>) -> Self {
+        match checked_range(x_range) {
+            Ok(range) => self.x_range = Some(range),
+            Err(e) => return self.fail(format!("x range: {}", e)),
+        }
+        self
+    }
+
+    /// Sets the initial interval of y, which is otherwise fitted to the curves
+    pub fn y_range(mut self, y_range: Range<f32>) -> Self {
+        match checked_range(y_range) {
+            Ok(range) => self.y_range = Some(range),
+            Err(e) => return self.fail(format!("y range: {}", e)),
+        }
+        self
+    }
+
+    pub fn grid(mut self, visible: bool) -> Self {
+        self.grid = visible;
+        self
+    }
+
+    pub fn time(mut self, t: f32) -> Self {
+        self.time = t;
+        self
+    }
+
+    /// Shows the given theme first, before the built-in ones
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.themes.retain(|other| other.name != theme.name);
+        self.themes.insert(0, theme);
+        self
+    }
+
+    /// Sets the size of the window and of the PNG and SVG files, neither side can be 0
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        if width == 0 || height == 0 {
+            return self.fail(format!("size: {}x{} has a side of 0 pixels", width, height));
+        }
+        self.window.size = (width, height);
+        self
+    }
+
+    /// Selects the device that draws the plot instead of the best ranked one
+    pub fn device(mut self, device: DeviceSelector) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// Opens a window that shows the plot, see `run_window`, and returns when it is closed
+    pub fn show(self) -> Result<(), FPlotError> {
+        self.check()?;
+        window::run_window(self)
+    }
+
+    /// Renders the plot offscreen with Vulkan and saves it as a PNG image of the size of the window
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), FPlotError> {
+        self.check()?;
+        let (width, height) = self.window.size;
+        self.render_frames(&[self.time], |_, pixels| {
+            raster::write_png(path.as_ref(), width, height, &pixels)
+        })
+    }
+
+    /**
+    Renders `frames` frames of the plot offscreen with Vulkan, the first one at `time` and each
+    one after `time_step` from the previous one, and saves them as an animated GIF image when the
+    extension of the path is `gif` or else as PNG images. The PNG images get the number of the
+    frame appended to their name, like `plot-0001.png`. When the y interval is not set it is fitted
+    to the curves over all the frames, so that the animation does not jump.
+    */
+    pub fn save_animation<P: AsRef<Path>>(
+        &self,
+        path: P,
+        frames: u32,
+        time_step: f32,
+    ) -> Result<(), FPlotError> {
+        self.check()?;
+        let path = path.as_ref();
+        let (width, height) = self.window.size;
+        let times = (0..frames)
+            .map(|frame| self.time + frame as f32 * time_step)
+            .collect::<Vec<_>>();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gif") => {
+                let mut gif = GifWriter::create(path, width, height, time_step)
+                    .map_err(FPlotError::Export)?;
+                self.render_frames(&times, |_, mut pixels| gif.write_frame(&mut pixels))
+            }
+            _ => self.render_frames(&times, |frame, pixels| {
+                let frame_path = numbered_path(path, frame, times.len());
+                raster::write_png(&frame_path, width, height, &pixels)
+            }),
+        }
+    }
+
+    /// Saves the plot as an SVG file of the size of the window
+    pub fn save_svg<P: AsRef<Path>>(&self, path: P) -> Result<(), FPlotError> {
+        self.check()?;
+        let (width, height) = self.window.size;
+        let size = Vector2::new(width as f64, height as f64);
+        svg::write_svg(path.as_ref(), &self.figure(self.initial_view(size), size))
+            .map_err(FPlotError::Export)
+    }
+
+    /// Saves the plot as a PDF document with the page of `pdf_page`
+    pub fn save_pdf<P: AsRef<Path>>(&self, path: P) -> Result<(), FPlotError> {
+        self.check()?;
+        let size = self.pdf_page.plot_size();
+        let figure = self.figure(self.initial_view(size), size);
+        pdf::write_pdf(path.as_ref(), &figure, &self.pdf_page).map_err(FPlotError::Export)
+    }
+
+    /// Reports the first error of the builder methods and the plots that cannot be drawn
+    fn check(&self) -> Result<(), FPlotError> {
+        match &self.error {
+            Some(e) => Err(FPlotError::InvalidPlot(e.clone())),
+            None if self.themes.is_empty() => Err(FPlotError::InvalidPlot(
+                "the plot has no themes".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The functions of x of the curves at the time of the plot
+    pub(crate) fn functions(&self) -> Vec<impl Fn(f32) -> f32 + '_> {
+        self.curves
+            .iter()
+            .map(|curve| curve.at(self.time))
+            .collect()
+    }
+
+    /// The view that shows the initial intervals in a window or image of the given size
+    pub(crate) fn initial_view(&self, size: Vector2<f64>) -> View {
+        self.initial_view_over(size, &[self.time])
+    }
+
+    /// The view that shows the initial intervals, with the y one fitted to the curves at all the
+    /// given times when it is not set
+    fn initial_view_over(&self, size: Vector2<f64>, times: &[f32]) -> View {
+        let mut view = View::default();
+        let x_range = self.x_range.unwrap_or_else(|| view.x_range(size));
+        match self.y_range {
+            Some((y_start, y_end)) => view.zoom_to_rect(
+                Vector2::new(x_range.0, y_start),
+                Vector2::new(x_range.1, y_end),
+                size,
+            ),
+            None => {
+                let functions = times
+                    .iter()
+                    .flat_map(|t| self.curves.iter().map(move |curve| curve.at(*t)))
+                    .collect::<Vec<_>>();
+                view.fit(&functions, x_range, size)
+            }
+        }
+        view
+    }
+
+    /**
+    Draws the plot offscreen at each of the given times, all the frames have the same view, and
+    gives the RGBA pixels of each one to `write` along with its index. The renderer is created
+    once, given the settings of the plot and prepared before the frames are drawn, then for each
+    frame the curves are sampled before the transform places the dashes along them.
+    */
+    fn render_frames<W>(&self, times: &[f32], mut write: W) -> Result<(), FPlotError>
+    where
+        W: FnMut(usize, Vec<u8>) -> Result<(), String>,
+    {
+        let (width, height) = self.window.size;
+        let size = Vector2::new(width as f64, height as f64);
+        let mut renderer =
+            GraphVk::new_headless(self.window.size, self.msaa_samples, self.device.as_ref())?;
+        self.apply_to_renderer(&mut renderer)?;
+        let view = self.initial_view_over(size, times);
+        let (x_start, x_end) = view.x_range(size);
+        for (frame, t) in times.iter().enumerate() {
+            let functions = self
+                .curves
+                .iter()
+                .map(|curve| curve.at(*t))
+                .collect::<Vec<_>>();
+            renderer.fill_graph_buffer(x_start, x_end, &functions);
+            renderer.set_transform(&view.center, &view.half_extent(size));
+            let pixels = renderer.render_offscreen()?;
+            write(frame, pixels).map_err(FPlotError::Export)?;
+        }
+        Ok(())
+    }
+
+    /// Gives the curves, the first theme and the grid of the plot to the renderer
+    pub(crate) fn apply_to_renderer(&self, renderer: &mut GraphVk) -> Result<(), FPlotError> {
+        renderer.set_curve_count(self.curves.len())?;
+        renderer.set_theme(self.themes[0].clone())?;
+        renderer.set_grid_visible(self.grid)?;
+        for (i, curve) in self.curves.iter().enumerate() {
+            renderer.set_curve_style(i, curve.style)?;
+            renderer.set_curve_color(i, curve.color)?;
+        }
+        renderer.prepare()
+    }
+
+    /// Description of the plot with the first theme and the given view for the exporters
+    fn figure(&self, view: View, size: Vector2<f64>) -> Figure<'_> {
+        let theme = &self.themes[0];
+        Figure {
+            view,
+            size,
+            theme,
+            grid: self.grid,
+            curves: self
+                .curves
+                .iter()
+                .enumerate()
+                .map(|(i, curve)| FigureCurve {
+                    function: Box::new(curve.at(self.time)),
+                    color: curve.color.unwrap_or_else(|| theme.curve_color(i)),
+                    style: curve.style,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The bounds of an interval, whose start must be lower than its end
+fn checked_range(range: Range<f32>) -> Result<(f32, f32), String> {
+    if range.start.is_finite() && range.end.is_finite() && range.start < range.end {
+        Ok((range.start, range.end))
+    } else {
+        Err(format!(
+            "{:?} is not a finite interval whose start is lower than its end",
+            range
+        ))
+    }
+}
+
+/// Path of a frame of a sequence, the file name gets the number of the frame with enough leading
+/// zeros to make all of them the same length, at least 4 digits
+fn numbered_path(path: &Path, frame: usize, frames: usize) -> PathBuf {
+    let digits = frames.saturating_sub(1).to_string().len().max(4);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut file_name = format!("{}-{:0width$}", stem, frame, width = digits);
+    if let Some(extension) = path.extension() {
+        file_name = format!("{}.{}", file_name, extension.to_string_lossy());
+    }
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+
+/// Environment variable with the device to use, in the format accepted by `DeviceSelector::parse`
+pub const DEVICE_ENV_VAR: &str = "FPLOT_DEVICE";
+
+/// Kind of a physical device, from the most to the least preferred
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum DeviceType {
+    Discrete,
+    Integrated,
+    Virtual,
+    Cpu,
+    Other,
+}
+
+impl DeviceType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeviceType::Discrete => "discrete",
+            DeviceType::Integrated => "integrated",
+            DeviceType::Virtual => "virtual",
+            DeviceType::Cpu => "cpu",
+            DeviceType::Other => "other",
+        }
+    }
+}
+
+/// A device found by the renderer and, when it cannot draw the plots, the reason why
+#[derive(Clone, Debug)]
+pub struct DeviceInfo {
+    /// Position of the device in the order the driver lists them
+    pub index: usize,
+    pub name: String,
+    /// PCI vendor ID, like 0x10de for NVIDIA
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub device_type: DeviceType,
+    /// Size in bytes of the memory heaps local to the device
+    pub local_memory: u64,
+    /// Queues of the family that the renderer would use
+    pub queue_count: u32,
+    pub rejection: Option<String>,
+}
+
+impl DeviceInfo {
+    pub fn is_usable(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Device chosen by the user instead of the best ranked one
+#[derive(Clone, PartialEq, Debug)]
+pub enum DeviceSelector {
+    /// Position of the device in the order the driver lists them
+    Index(usize),
+    /// PCI vendor ID of the device
+    VendorId(u32),
+    /// Part of the name of the device, compared ignoring the case
+    Name(String),
+}
+
+impl DeviceSelector {
+    /// Parses a decimal index, a vendor ID in hexadecimal like `0x10de` or else part of a name
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("expected an index, a vendor ID or part of a name".to_string());
+        }
+        if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            return u32::from_str_radix(hex, 16)
+                .map(DeviceSelector::VendorId)
+                .map_err(|_| format!("{} is not a hexadecimal vendor ID", text));
+        }
+        Ok(match text.parse::<usize>() {
+            Ok(index) => DeviceSelector::Index(index),
+            Err(_) => DeviceSelector::Name(text.to_string()),
+        })
+    }
+
+    /// The selector of the `FPLOT_DEVICE` environment variable, an invalid one is reported with a
+    /// warning and ignored
+    pub fn from_env() -> Option<Self> {
+        let text = std::env::var(DEVICE_ENV_VAR).ok()?;
+        DeviceSelector::parse(&text)
+            .map_err(|e| eprintln!("Warning: {}: {}", DEVICE_ENV_VAR, e))
+            .ok()
+    }
+
+    pub fn matches(&self, device: &DeviceInfo) -> bool {
+        match self {
+            DeviceSelector::Index(index) => device.index == *index,
+            DeviceSelector::VendorId(vendor_id) => device.vendor_id == *vendor_id,
+            DeviceSelector::Name(name) => device.name.to_lowercase().contains(&name.to_lowercase()),
+        }
+    }
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeviceSelector::Index(index) => write!(f, "index {}", index),
+            DeviceSelector::VendorId(vendor_id) => write!(f, "vendor ID {:#06x}", vendor_id),
+            DeviceSelector::Name(name) => write!(f, "name \"{}\"", name),
+        }
+    }
+}
+
+/**
+Index in `devices` of the device to use. Without a selector it is the best ranked of the usable
+devices: discrete GPUs come first, then integrated, virtual and CPU ones, and devices of the same
+type are ordered by the size of their local memory and then by the number of queues. With a
+selector it is the best ranked of the devices that match it, which is an error when none matches
+or when the ones that match cannot be used.
+*/
+pub fn select_device(
+    devices: &[DeviceInfo],
+    selector: Option<&DeviceSelector>,
+) -> Result<usize, String> {
+    let mut candidates = (0..devices.len())
+        .filter(|i| match selector {
+            Some(selector) => selector.matches(&devices[*i]),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| {
+        let (a, b) = (&devices[*a], &devices[*b]);
+        a.device_type
+            .cmp(&b.device_type)
+            .then(b.local_memory.cmp(&a.local_memory))
+            .then(b.queue_count.cmp(&a.queue_count))
+    });
+    if let Some(selected) = candidates.iter().find(|i| devices[**i].is_usable()) {
+        return Ok(*selected);
+    }
+
+    // the reasons of the rejections tell what is missing
+    let rejections = candidates
+        .iter()
+        .map(|i| &devices[*i])
+        .map(|device| format!("{}: {}", device.name, device.rejection.as_ref().unwrap()))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(match selector {
+        None if devices.is_empty() => "no device found".to_string(),
+        None => format!(
+            "none of the {} devices found can be used, {}",
+            devices.len(),
+            rejections
+        ),
+        Some(selector) if candidates.is_empty() => {
+            format!(
+                "none of the {} devices found matches the {}",
+                devices.len(),
+                selector
+            )
+        }
+        Some(selector) => format!(
+            "the devices that match the {} cannot be used, {}",
+            selector, rejections
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A usable device with the given ranking criteria, named after its index
+    fn device(index: usize, device_type: DeviceType, local_memory: u64, queues: u32) -> DeviceInfo {
+        DeviceInfo {
+            index,
+            name: format!("GPU {}", index),
+            vendor_id: 0x1002,
+            device_id: 0,
+            device_type,
+            local_memory,
+            queue_count: queues,
+            rejection: None,
+        }
+    }
+
+    fn rejected(mut device: DeviceInfo, reason: &str) -> DeviceInfo {
+        device.rejection = Some(reason.to_string());
+        device
+    }
+
+    #[test]
+    fn parse_tells_apart_indices_vendor_ids_and_names() {
+        assert_eq!(DeviceSelector::parse("1"), Ok(DeviceSelector::Index(1)));
+        assert_eq!(
+            DeviceSelector::parse("0x10de"),
+            Ok(DeviceSelector::VendorId(0x10de))
+        );
+        assert_eq!(
+            DeviceSelector::parse(" 0X10DE "),
+            Ok(DeviceSelector::VendorId(0x10de))
+        );
+        assert_eq!(
+            DeviceSelector::parse("GeForce"),
+            Ok(DeviceSelector::Name("GeForce".to_string()))
+        );
+        assert!(DeviceSelector::parse("0xnvidia").is_err());
+        assert!(DeviceSelector::parse("  ").is_err());
+    }
+
+    #[test]
+    fn matches_compares_names_ignoring_the_case() {
+        let mut nvidia = device(3, DeviceType::Discrete, 0, 1);
+        nvidia.name = "NVIDIA GeForce RTX 3060".to_string();
+        nvidia.vendor_id = 0x10de;
+        assert!(DeviceSelector::Index(3).matches(&nvidia));
+        assert!(!DeviceSelector::Index(1).matches(&nvidia));
+        assert!(DeviceSelector::VendorId(0x10de).matches(&nvidia));
+        assert!(!DeviceSelector::VendorId(0x1002).matches(&nvidia));
+        assert!(DeviceSelector::Name("geforce".to_string()).matches(&nvidia));
+        assert!(!DeviceSelector::Name("radeon".to_string()).matches(&nvidia));
+    }
+
+    #[test]
+    fn select_device_ranks_by_type_then_memory_then_queues() {
+        let devices = [
+            device(0, DeviceType::Cpu, 1 << 40, 16),
+            device(1, DeviceType::Integrated, 1 << 30, 1),
+            device(2, DeviceType::Discrete, 1 << 30, 1),
+            device(3, DeviceType::Discrete, 1 << 32, 1),
+            device(4, DeviceType::Discrete, 1 << 32, 4),
+        ];
+        assert_eq!(select_device(&devices, None), Ok(4));
+        assert_eq!(select_device(&devices[..4], None), Ok(3));
+        assert_eq!(select_device(&devices[..3], None), Ok(2));
+        assert_eq!(select_device(&devices[..2], None), Ok(1));
+        assert_eq!(select_device(&devices[..1], None), Ok(0));
+    }
+
+    #[test]
+    fn select_device_skips_the_devices_that_cannot_be_used() {
+        let devices = [
+            rejected(
+                device(0, DeviceType::Discrete, 1 << 32, 1),
+                "missing VK_KHR_swapchain",
+            ),
+            device(1, DeviceType::Integrated, 1 << 30, 1),
+        ];
+        assert_eq!(select_device(&devices, None), Ok(1));
+        assert_eq!(
+            select_device(&devices, Some(&DeviceSelector::Index(1))),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn select_device_picks_the_best_of_the_matching_devices() {
+        let mut devices = [
+            device(0, DeviceType::Discrete, 1 << 32, 1),
+            device(1, DeviceType::Integrated, 1 << 30, 1),
+            device(2, DeviceType::Discrete, 1 << 30, 1),
+        ];
+        devices[1].vendor_id = 0x10de;
+        devices[2].vendor_id = 0x10de;
+        let nvidia = DeviceSelector::parse("0x10de").unwrap();
+        assert_eq!(select_device(&devices, Some(&nvidia)), Ok(2));
+        let second = DeviceSelector::parse("1").unwrap();
+        assert_eq!(select_device(&devices, Some(&second)), Ok(1));
+        let by_name = DeviceSelector::parse("gpu 0").unwrap();
+        assert_eq!(select_device(&devices, Some(&by_name)), Ok(0));
+    }
+
+    #[test]
+    fn select_device_reports_why_no_device_is_selected() {
+        let devices = [
+            rejected(
+                device(0, DeviceType::Discrete, 1 << 32, 1),
+                "missing VK_KHR_swapchain",
+            ),
+            device(1, DeviceType::Integrated, 1 << 30, 1),
+        ];
+        assert_eq!(
+            select_device(&devices, Some(&DeviceSelector::Index(0))),
+            Err("the devices that match the index 0 cannot be used, GPU 0: missing VK_KHR_swapchain"
+                .to_string())
+        );
+        assert_eq!(
+            select_device(&devices, Some(&DeviceSelector::VendorId(0x10de))),
+            Err("none of the 2 devices found matches the vendor ID 0x10de".to_string())
+        );
+        assert_eq!(
+            select_device(&devices, Some(&DeviceSelector::Name("Radeon".to_string()))),
+            Err("none of the 2 devices found matches the name \"Radeon\"".to_string())
+        );
+        assert_eq!(
+            select_device(&devices[..1], None),
+            Err(
+                "none of the 1 devices found can be used, GPU 0: missing VK_KHR_swapchain"
+                    .to_string()
+            )
+        );
+        assert_eq!(select_device(&[], None), Err("no device found".to_string()));
+    }
+}
@@ -1,2 +1,6 @@
-pub mod vk;
-pub mod window_manager;
+pub mod device;
+pub mod present_mode;
+pub mod style;
+pub(crate) mod vk;
+pub mod vulkan_info;
+pub(crate) mod window_manager;
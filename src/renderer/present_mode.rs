@@ -0,0 +1,28 @@
+/// How the frames are handed to the display, when the device does not support the requested mode
+/// the renderer falls back to `Fifo`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PresentMode {
+    /// Waits for the vertical blank, never tears
+    Fifo,
+    /// Like `Fifo` but late frames are shown immediately and can tear
+    FifoRelaxed,
+    /// Replaces the queued frame with the newest one, never tears and has low latency
+    #[default]
+    Mailbox,
+    /// Shows the frames as soon as they are ready, can tear
+    Immediate,
+}
+
+pub const PRESENT_MODE_NAMES: [&str; 4] = ["fifo", "fifo_relaxed", "mailbox", "immediate"];
+
+impl PresentMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fifo" => Some(PresentMode::Fifo),
+            "fifo_relaxed" => Some(PresentMode::FifoRelaxed),
+            "mailbox" => Some(PresentMode::Mailbox),
+            "immediate" => Some(PresentMode::Immediate),
+            _ => None,
+        }
+    }
+}
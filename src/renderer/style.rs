@@ -0,0 +1,62 @@
+/// Pattern of the dashes along a line, the custom lengths are in pixels
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DashPattern {
+    Solid,
+    Dashed,
+    Dotted,
+    Custom { dash: f32, gap: f32 },
+}
+
+impl DashPattern {
+    /// Length of the dashes and of the gaps between them for a line of the given width, the
+    /// predefined patterns grow with the width so that they keep their look. Solid lines have
+    /// no gaps
+    pub fn lengths(&self, width: f32) -> (f32, f32) {
+        match *self {
+            DashPattern::Solid => (0.0f32, 0.0f32),
+            DashPattern::Dashed => (6.0f32 * width.max(1.0f32), 4.0f32 * width.max(1.0f32)),
+            DashPattern::Dotted => (width.max(1.0f32), 2.0f32 * width.max(1.0f32)),
+            DashPattern::Custom { dash, gap } => (dash, gap),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "solid" => Some(DashPattern::Solid),
+            "dashed" => Some(DashPattern::Dashed),
+            "dotted" => Some(DashPattern::Dotted),
+            _ => None,
+        }
+    }
+}
+
+/// Appearance of a line, the width is in pixels
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LineStyle {
+    pub width: f32,
+    pub pattern: DashPattern,
+}
+
+impl LineStyle {
+    pub const AXIS: LineStyle = LineStyle {
+        width: 1.0f32,
+        pattern: DashPattern::Solid,
+    };
+    pub const GRID: LineStyle = LineStyle {
+        width: 1.0f32,
+        pattern: DashPattern::Solid,
+    };
+    pub const SELECTION: LineStyle = LineStyle {
+        width: 1.0f32,
+        pattern: DashPattern::Dashed,
+    };
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        LineStyle {
+            width: 2.0f32,
+            pattern: DashPattern::Solid,
+        }
+    }
+}
@@ -1,11 +1,17 @@
 use super::pointer_chain_helpers::*;
 use super::vk_debug_callback;
+use crate::error::FPlotError;
+use crate::renderer::device::{self, DeviceInfo, DeviceSelector, DeviceType};
+use crate::renderer::vulkan_info::{
+    ExtensionInfo, FeatureStructInfo, LayerInfo, MemoryHeapInfo, PhysicalDeviceInfo,
+    QueueFamilyInfo, Version, VulkanInfo,
+};
 use ash::{extensions::*, vk};
 use gpu_allocator::{vulkan as vkalloc, MemoryLocation};
-use std::borrow::Borrow;
 use std::collections::HashSet;
 use std::ffi::{CStr, CString};
 use std::mem::ManuallyDrop;
+use std::os::raw::c_char;
 
 use raw_window_handle::RawWindowHandle;
 
@@ -22,6 +28,8 @@ pub struct BaseVk {
     pub swapchain_create_info: Option<vk::SwapchainCreateInfoKHR>,
     pub swapchain: vk::SwapchainKHR,
     pub swapchain_image_views: Option<Vec<vk::ImageView>>,
+    // Required extensions followed by the optional ones that the device supports
+    device_extensions: Vec<CString>,
     pub allocator: ManuallyDrop<gpu_allocator::vulkan::Allocator>,
     #[cfg(debug_assertions)]
     debug_utils_fn: ext::DebugUtils,
@@ -53,6 +61,21 @@ pub struct DescriptorInfo {
     pub buffers: Vec<vk::DescriptorSet>,
 }
 
+/**
+Extensions, features and queues requested to the device. A device is used only if it supports the
+required ones, while the optional ones are enabled only when the device supports them: the features
+of `optional_features` that it does not support are disabled when creating it, so that the chain
+tells which ones have been enabled, and `BaseVk::is_device_extension_enabled` tells the same about
+the optional extensions.
+*/
+pub struct DeviceRequirements<'a> {
+    pub extensions: &'a [&'a str],
+    pub optional_extensions: &'a [&'a str],
+    pub features: &'a DeviceFeaturesChain,
+    pub optional_features: &'a mut DeviceFeaturesChain,
+    pub queues: &'a [(vk::QueueFlags, f32)],
+}
+
 /**
 BaseVk is struct that initializes a single Vulkan 1.1 instance and device with optional surface support.
 It supports instance creation with extensions and device selection with Vulkan 1.1 features
@@ -60,75 +83,63 @@ and requested queues. It also initializes an allocator that greatly simplifies V
 Basically it is a bootstrap for a very common vulkan setup.
 */
 impl BaseVk {
+    /**
+    Creates the instance, the surface of the window when a handle is given, and the device with
+    the required extensions, features and queues and the optional ones it supports. The objects
+    created before a step that fails are destroyed.
+    */
     pub fn new(
         application_name: &str,
         instance_extensions: &[&str],
-        device_extensions: &[&str],
-        desired_physical_device_features2: &vk::PhysicalDeviceFeatures2,
-        desired_queues: &[(vk::QueueFlags, f32)],
+        requirements: DeviceRequirements,
         window_handle: Option<RawWindowHandle>,
-    ) -> Self {
-        let application_name = CString::new(application_name).unwrap();
-        let application_info = vk::ApplicationInfo::builder()
-            .application_name(application_name.as_c_str())
-            .application_version(vk::make_api_version(0, 0, 1, 0))
-            .engine_name(CStr::from_bytes_with_nul(b"TheVulkanTemple\0").unwrap())
-            .engine_version(vk::make_api_version(0, 0, 1, 0))
-            .api_version(vk::API_VERSION_1_1);
-
+        device_selector: Option<&DeviceSelector>,
+    ) -> Result<Self, FPlotError> {
         let mut instance_extensions: Vec<CString> = instance_extensions
             .iter()
             .map(|s| CString::new(*s).unwrap())
             .collect();
 
+        // The validation is enabled only when the layers of the SDK are installed
         cfg_if::cfg_if! {
             if #[cfg(debug_assertions)] {
-                instance_extensions.push(CString::new("VK_EXT_debug_utils").unwrap());
-                let validation_layer_name = CStr::from_bytes_with_nul(b"VK_LAYER_KHRONOS_validation\0")
-                    .unwrap()
-                    .as_ptr();
-                let layer_names = [validation_layer_name];
+                let optional_layers = ["VK_LAYER_KHRONOS_validation"];
+                let optional_extensions = ["VK_EXT_debug_utils"];
             } else {
-                let layer_names = [];
+                let optional_layers = [];
+                let optional_extensions = [];
             }
         }
 
         // adding the required extensions needed for creating a surface based on the os
         if let Some(handle) = window_handle {
-            instance_extensions.push(CString::new("VK_KHR_surface").unwrap());
-            match handle {
-                RawWindowHandle::Win32(_) => {
-                    instance_extensions.push(CString::new("VK_KHR_win32_surface").unwrap());
-                }
-                RawWindowHandle::Xlib(_) => {
-                    instance_extensions.push(CString::new("VK_KHR_xlib_surface").unwrap());
-                }
-                RawWindowHandle::Wayland(_) => {
-                    instance_extensions.push(CString::new("VK_KHR_wayland_surface").unwrap());
-                }
-                _ => {
-                    panic!("Unrecognized window handle")
-                }
-            };
+            for extension in Self::surface_extensions(handle)? {
+                instance_extensions.push(CString::new(extension).unwrap());
+            }
         }
+
+        let entry_fn =
+            unsafe { ash::Entry::load() }.map_err(|e| FPlotError::Loader(e.to_string()))?;
+        let (layer_names, instance_extensions) = Self::select_instance_layers_and_extensions(
+            &entry_fn,
+            instance_extensions,
+            &optional_layers,
+            &optional_extensions,
+        )?;
+        let layer_names_ptrs = layer_names.iter().map(|s| s.as_ptr()).collect::<Vec<_>>();
         let instance_extensions_ptrs = instance_extensions
             .iter()
             .map(|s| s.as_ptr())
             .collect::<Vec<_>>();
+        let instance = Self::create_instance(
+            &entry_fn,
+            application_name,
+            &layer_names_ptrs,
+            &instance_extensions_ptrs,
+            vk::API_VERSION_1_1,
+        )?;
 
-        let instance_create_info = vk::InstanceCreateInfo::builder()
-            .application_info(&application_info)
-            .enabled_layer_names(&layer_names)
-            .enabled_extension_names(&instance_extensions_ptrs);
-
-        let entry_fn = unsafe { ash::Entry::load().unwrap() };
-        let instance = unsafe {
-            entry_fn
-                .create_instance(&instance_create_info, None)
-                .expect("Could not create VkInstance")
-        };
-
-        // Creation of an optional debug reporter
+        // Creation of an optional debug reporter, without it the validation messages are lost
         cfg_if::cfg_if! {
             if #[cfg(debug_assertions)] {
                 let debug_utils_messenger_create_info = vk::DebugUtilsMessengerCreateInfoEXT::builder()
@@ -144,50 +155,50 @@ impl BaseVk {
                     )
                     .pfn_user_callback(Some(vk_debug_callback));
                 let debug_utils_fn = ext::DebugUtils::new(&entry_fn, &instance);
-                let debug_utils_messenger = unsafe {
-                    debug_utils_fn
-                        .create_debug_utils_messenger(&debug_utils_messenger_create_info, None)
-                        .unwrap()
+                let debug_utils_enabled = instance_extensions
+                    .iter()
+                    .any(|extension| extension.as_bytes() == b"VK_EXT_debug_utils");
+                // the functions of a disabled extension cannot be called, not even to destroy
+                let debug_utils_messenger = match debug_utils_enabled {
+                    true => unsafe {
+                        debug_utils_fn
+                            .create_debug_utils_messenger(&debug_utils_messenger_create_info, None)
+                            .unwrap_or_else(|e| {
+                                eprintln!("Warning: cannot create the debug messenger: {}", e);
+                                vk::DebugUtilsMessengerEXT::null()
+                            })
+                    },
+                    false => vk::DebugUtilsMessengerEXT::null(),
                 };
             }
         }
+        // Destroys the instance level objects when a later step fails, null handles are ignored
+        let destroy_instance = |surface: vk::SurfaceKHR| unsafe {
+            if surface != vk::SurfaceKHR::null() {
+                khr::Surface::new(&entry_fn, &instance).destroy_surface(surface, None);
+            }
+            #[cfg(debug_assertions)]
+            if debug_utils_messenger != vk::DebugUtilsMessengerEXT::null() {
+                debug_utils_fn.destroy_debug_utils_messenger(debug_utils_messenger, None);
+            }
+            instance.destroy_instance(None);
+        };
 
         // Creating the surface based on os
-        let surface = unsafe {
-            match window_handle {
-                Some(RawWindowHandle::Win32(handle)) => {
-                    let surface_desc = vk::Win32SurfaceCreateInfoKHR::builder()
-                        .hinstance(handle.hinstance)
-                        .hwnd(handle.hwnd);
-                    let win_surface_fn = khr::Win32Surface::new(&entry_fn, &instance);
-                    win_surface_fn
-                        .create_win32_surface(&surface_desc, None)
-                        .unwrap()
-                }
-                Some(RawWindowHandle::Xlib(handle)) => {
-                    let surface_desc = vk::XlibSurfaceCreateInfoKHR::builder()
-                        .dpy(handle.display as *mut _)
-                        .window(handle.window);
-                    let xlib_surface_fn = khr::XlibSurface::new(&entry_fn, &instance);
-                    xlib_surface_fn
-                        .create_xlib_surface(&surface_desc, None)
-                        .unwrap()
-                }
-                Some(RawWindowHandle::Wayland(handle)) => {
-                    let surface_desc = vk::WaylandSurfaceCreateInfoKHR::builder()
-                        .display(handle.display)
-                        .surface(handle.surface);
-                    let wayland_surface_fn = khr::WaylandSurface::new(&entry_fn, &instance);
-                    wayland_surface_fn
-                        .create_wayland_surface(&surface_desc, None)
-                        .unwrap()
-                }
-                None => vk::SurfaceKHR::null(),
-                _ => panic!("Unsupported window handle"),
+        let surface = match window_handle {
+            Some(handle) => unsafe { Self::create_surface(&entry_fn, &instance, handle) },
+            None => Ok(vk::SurfaceKHR::null()),
+        };
+        let surface = match surface {
+            Ok(surface) => surface,
+            Err(e) => {
+                destroy_instance(vk::SurfaceKHR::null());
+                return Err(FPlotError::Surface(e.to_string()));
             }
         };
 
-        let mut desired_device_extensions: Vec<CString> = device_extensions
+        let mut desired_device_extensions: Vec<CString> = requirements
+            .extensions
             .iter()
             .map(|s| CString::new(*s).unwrap())
             .collect();
@@ -197,107 +208,72 @@ impl BaseVk {
             desired_device_extensions.push(CString::new("VK_KHR_swapchain").unwrap());
         }
 
-        // Creating a new struct pointer chain to accommodate the features of the physical devices
-        let mut available_device_features = unsafe {
-            clone_vk_physical_device_features2_structure(desired_physical_device_features2)
+        let physical_devices = match unsafe { instance.enumerate_physical_devices() } {
+            Ok(physical_devices) => physical_devices,
+            Err(e) => {
+                destroy_instance(surface);
+                return Err(FPlotError::DeviceSelection(e.to_string()));
+            }
         };
 
-        // Iterate for all physical devices and keep only those that respect our requirements
-        let good_devices;
+        // The best ranked of the devices that meet the requirements, unless the user chose another
+        let (devices, queue_family_indices) = unsafe {
+            Self::evaluate_physical_devices(
+                &instance,
+                &physical_devices,
+                &desired_device_extensions,
+                requirements.features,
+                requirements.queues,
+                surface_fn.as_ref().map(|surface_fn| (surface_fn, surface)),
+            )
+        };
+        let selected_device = match device::select_device(&devices, device_selector) {
+            Ok(i) => (physical_devices[i], queue_family_indices[i]),
+            Err(e) => {
+                destroy_instance(surface);
+                return Err(FPlotError::DeviceSelection(e));
+            }
+        };
+
+        // Adding the optional extensions and features that the selected device supports
+        let mut enabled_device_features;
         unsafe {
-            good_devices = instance
-                .enumerate_physical_devices()
-                .unwrap()
+            let extensions = instance
+                .enumerate_device_extension_properties(selected_device.0)
+                .unwrap_or_default();
+            let extensions_names: HashSet<&CStr> = extensions
                 .iter()
-                .filter_map(|physical_device| {
-                    // Check if the physical device supports the required extensions
-                    let extensions = instance
-                        .enumerate_device_extension_properties(*physical_device)
-                        .unwrap();
-                    let extensions_names: HashSet<&CStr> = extensions
-                        .iter()
-                        .map(|v| CStr::from_ptr(v.extension_name.as_ptr()))
-                        .collect();
-                    if !desired_device_extensions
-                        .iter()
-                        .all(|e| extensions_names.contains(e.as_c_str()))
-                    {
-                        return None;
-                    }
-
-                    // Check if the physical device supports the features requested
-                    instance.get_physical_device_features2(
-                        *physical_device,
-                        &mut available_device_features,
-                    );
-                    available_device_features.features.robust_buffer_access = 300;
-                    if !compare_vk_physical_device_features2(
-                        &available_device_features,
-                        desired_physical_device_features2,
-                    ) {
-                        return None;
-                    }
+                .map(|v| CStr::from_ptr(v.extension_name.as_ptr()))
+                .collect();
+            desired_device_extensions.extend(
+                requirements
+                    .optional_extensions
+                    .iter()
+                    .map(|s| CString::new(*s).unwrap())
+                    .filter(|e| extensions_names.contains(e.as_c_str())),
+            );
 
-                    // Check if the physical device supports the requested queues
-                    let mut queue_family_properties = Vec::<vk::QueueFamilyProperties2>::new();
-                    queue_family_properties.resize(
-                        instance.get_physical_device_queue_family_properties2_len(*physical_device),
-                        vk::QueueFamilyProperties2::default(),
-                    );
-                    instance.get_physical_device_queue_family_properties2(
-                        *physical_device,
-                        &mut queue_family_properties,
-                    );
-                    let good_family_queues =
-                        queue_family_properties
-                            .iter()
-                            .enumerate()
-                            .find(|(i, queue_family)| {
-                                let mut is_family_queue_good = desired_queues.iter().all(|q| {
-                                    queue_family
-                                        .queue_family_properties
-                                        .queue_flags
-                                        .contains(q.0)
-                                });
-                                is_family_queue_good = is_family_queue_good
-                                    && desired_queues.len()
-                                        <= queue_family.queue_family_properties.queue_count
-                                            as usize;
-
-                                if surface != vk::SurfaceKHR::null() {
-                                    is_family_queue_good = is_family_queue_good
-                                        && surface_fn
-                                            .as_ref()
-                                            .unwrap()
-                                            .get_physical_device_surface_support(
-                                                *physical_device,
-                                                *i as u32,
-                                                surface,
-                                            )
-                                            .unwrap();
-                                }
-                                is_family_queue_good
-                            });
-
-                    if let Some(selected_family_queue) = good_family_queues {
-                        return Some((*physical_device, selected_family_queue.0 as u32));
-                    }
-                    None
-                })
-                .collect::<Vec<(vk::PhysicalDevice, u32)>>();
-            destroy_vk_physical_device_features2(&mut available_device_features);
-        }
+            let mut available_device_features = requirements.optional_features.without_features();
+            instance.get_physical_device_features2(
+                selected_device.0,
+                available_device_features.features2_mut(),
+            );
+            requirements
+                .optional_features
+                .intersect(&available_device_features);
 
-        if good_devices.len() > 1 {
-            println!("More than one device available selecting the first");
+            enabled_device_features = requirements.features.clone();
+            enabled_device_features.merge(requirements.optional_features);
         }
-        // Always selecting the first available device might not be the best strategy
-        let selected_device = good_devices.first().expect("No available device found");
 
         // Device creation
         let device;
         unsafe {
-            let queue_priorities = desired_queues.iter().map(|q| q.1).collect::<Vec<f32>>();
+            let queue_priorities = requirements
+                .queues
+                .iter()
+                .map(|q| q.1)
+                .collect::<Vec<f32>>();
             let queues_create_info = vk::DeviceQueueCreateInfo::builder()
                 .queue_family_index(selected_device.1)
                 .queue_priorities(&queue_priorities)
@@ -309,35 +285,52 @@ impl BaseVk {
             let mut device_create_info = vk::DeviceCreateInfo::builder()
                 .queue_create_infos(std::slice::from_ref(&queues_create_info))
                 .enabled_extension_names(&device_extensions_ptrs)
-                .enabled_features(&desired_physical_device_features2.features);
-            device_create_info.p_next = desired_physical_device_features2.p_next;
+                .enabled_features(&enabled_device_features.features2().features);
+            device_create_info.p_next = enabled_device_features.features2().p_next;
 
-            device = instance
-                .create_device(selected_device.0, &device_create_info, None)
-                .expect("Error creating device");
+            let created_device =
+                instance.create_device(selected_device.0, &device_create_info, None);
+            drop(enabled_device_features);
+            device = match created_device {
+                Ok(device) => device,
+                Err(e) => {
+                    destroy_instance(surface);
+                    return Err(FPlotError::DeviceSelection(format!(
+                        "cannot create the device: {}",
+                        e
+                    )));
+                }
+            };
         }
 
+        let allocator =
+            gpu_allocator::vulkan::Allocator::new(&gpu_allocator::vulkan::AllocatorCreateDesc {
+                instance: instance.clone(),
+                device: device.clone(),
+                physical_device: selected_device.0,
+                debug_settings: Default::default(),
+                buffer_device_address: false,
+            });
+        let allocator = match allocator {
+            Ok(allocator) => allocator,
+            Err(e) => {
+                unsafe { device.destroy_device(None) };
+                destroy_instance(surface);
+                return Err(FPlotError::Allocation(e.to_string()));
+            }
+        };
+
         let mut swapchain_fn = None;
         if window_handle.is_some() {
             swapchain_fn = Some(khr::Swapchain::new(&instance, &device));
         }
 
         let mut queues = Vec::new();
-        for i in 0..desired_queues.len() as u32 {
+        for i in 0..requirements.queues.len() as u32 {
             queues.push(unsafe { device.get_device_queue(selected_device.1, i) });
         }
 
-        let allocator =
-            gpu_allocator::vulkan::Allocator::new(&gpu_allocator::vulkan::AllocatorCreateDesc {
-                instance: instance.clone(),
-                device: device.clone(),
-                physical_device: selected_device.0,
-                debug_settings: Default::default(),
-                buffer_device_address: false,
-            })
-            .expect("Could not create Allocator");
-
-        BaseVk {
+        Ok(BaseVk {
             entry_fn,
             instance,
             surface,
@@ -350,12 +343,601 @@ impl BaseVk {
             swapchain_create_info: None,
             swapchain: vk::SwapchainKHR::null(),
             swapchain_image_views: None,
+            device_extensions: desired_device_extensions,
             allocator: ManuallyDrop::new(allocator),
             #[cfg(debug_assertions)]
             debug_utils_fn,
             #[cfg(debug_assertions)]
             debug_utils_messenger,
+        })
+    }
+
+    /**
+    Describes the physical devices and checks whether they support the extensions, the features
+    and the queues, like when creating a BaseVk without a surface: whether a device can present to
+    a window is not checked.
+    */
+    pub fn list_devices(
+        application_name: &str,
+        requirements: &DeviceRequirements,
+    ) -> Result<Vec<DeviceInfo>, FPlotError> {
+        let entry_fn =
+            unsafe { ash::Entry::load() }.map_err(|e| FPlotError::Loader(e.to_string()))?;
+        let instance =
+            Self::create_instance(&entry_fn, application_name, &[], &[], vk::API_VERSION_1_1)?;
+        let desired_device_extensions: Vec<CString> = requirements
+            .extensions
+            .iter()
+            .map(|s| CString::new(*s).unwrap())
+            .collect();
+        let devices = unsafe { instance.enumerate_physical_devices() }
+            .map(|physical_devices| unsafe {
+                Self::evaluate_physical_devices(
+                    &instance,
+                    &physical_devices,
+                    &desired_device_extensions,
+                    requirements.features,
+                    requirements.queues,
+                    None,
+                )
+                .0
+            })
+            .map_err(|e| FPlotError::DeviceSelection(e.to_string()));
+        unsafe { instance.destroy_instance(None) };
+        devices
+    }
+
+    /**
+    Reports the version, the layers and the extensions of the Vulkan implementation and what each
+    physical device supports, along with whether it meets the requirements like in `list_devices`.
+    When a window handle is given its surface is used to query the formats and the present modes,
+    a failure to create it is reported in `VulkanInfo::surface_error`.
+    */
+    pub fn vulkan_info(
+        application_name: &str,
+        requirements: &DeviceRequirements,
+        window_handle: Option<RawWindowHandle>,
+    ) -> Result<VulkanInfo, FPlotError> {
+        let entry_fn =
+            unsafe { ash::Entry::load() }.map_err(|e| FPlotError::Loader(e.to_string()))?;
+        let instance_version = match entry_fn.try_enumerate_instance_version() {
+            Ok(Some(version)) => version,
+            // without vkEnumerateInstanceVersion the implementation is Vulkan 1.0
+            Ok(None) => vk::API_VERSION_1_0,
+            Err(e) => return Err(FPlotError::Instance(e.to_string())),
+        };
+        let layers = entry_fn
+            .enumerate_instance_layer_properties()
+            .map_err(|e| FPlotError::Instance(format!("cannot list the layers: {}", e)))?
+            .iter()
+            .map(|layer| unsafe {
+                LayerInfo {
+                    name: c_chars_to_string(&layer.layer_name),
+                    spec_version: version_from_raw(layer.spec_version),
+                    implementation_version: layer.implementation_version,
+                    description: c_chars_to_string(&layer.description),
+                }
+            })
+            .collect::<Vec<_>>();
+        let extensions = entry_fn
+            .enumerate_instance_extension_properties()
+            .map_err(|e| FPlotError::Instance(format!("cannot list the extensions: {}", e)))?;
+        let extensions = extensions_info(&extensions);
+
+        // The surface extensions are enabled only when the window can use them
+        let mut surface_error = None;
+        let mut instance_extensions = Vec::new();
+        match window_handle.map(Self::surface_extensions) {
+            Some(Ok(surface_extensions)) => {
+                let missing = surface_extensions
+                    .iter()
+                    .filter(|name| !extensions.iter().any(|e| e.name == **name))
+                    .copied()
+                    .collect::<Vec<_>>();
+                match missing.is_empty() {
+                    true => instance_extensions.extend(
+                        surface_extensions
+                            .iter()
+                            .map(|name| CString::new(*name).unwrap()),
+                    ),
+                    false => {
+                        surface_error = Some(format!(
+                            "the instance extensions {} are missing",
+                            missing.join(", ")
+                        ))
+                    }
+                }
+            }
+            Some(Err(e)) => surface_error = Some(e.to_string()),
+            None => surface_error = Some("no window has been created".to_string()),
+        }
+        let instance_extensions_ptrs = instance_extensions
+            .iter()
+            .map(|s| s.as_ptr())
+            .collect::<Vec<_>>();
+        // the features structs of the core versions can be queried only when the instance uses
+        // that version of Vulkan, the newest one known is asked for
+        let api_version = instance_version.clamp(vk::API_VERSION_1_1, vk::API_VERSION_1_2);
+        let instance = Self::create_instance(
+            &entry_fn,
+            application_name,
+            &[],
+            &instance_extensions_ptrs,
+            api_version,
+        )?;
+
+        let mut surface = None;
+        if let (Some(handle), None) = (window_handle, &surface_error) {
+            match unsafe { Self::create_surface(&entry_fn, &instance, handle) } {
+                Ok(handle) => surface = Some((khr::Surface::new(&entry_fn, &instance), handle)),
+                Err(e) => surface_error = Some(format!("cannot create the surface: {}", e)),
+            }
+        }
+
+        // Devices that present to a window also need a swapchain
+        let mut desired_device_extensions: Vec<CString> = requirements
+            .extensions
+            .iter()
+            .map(|s| CString::new(*s).unwrap())
+            .collect();
+        if surface.is_some() {
+            desired_device_extensions.push(CString::new("VK_KHR_swapchain").unwrap());
+        }
+        let surface_ref = surface
+            .as_ref()
+            .map(|(surface_fn, surface)| (surface_fn, *surface));
+        let devices = unsafe { instance.enumerate_physical_devices() }
+            .map(|physical_devices| unsafe {
+                let (devices, _) = Self::evaluate_physical_devices(
+                    &instance,
+                    &physical_devices,
+                    &desired_device_extensions,
+                    requirements.features,
+                    requirements.queues,
+                    surface_ref,
+                );
+                physical_devices
+                    .iter()
+                    .zip(devices)
+                    .map(|(physical_device, device)| {
+                        Self::physical_device_info(
+                            &instance,
+                            api_version,
+                            *physical_device,
+                            device,
+                            surface_ref,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .map_err(|e| FPlotError::DeviceSelection(e.to_string()));
+
+        unsafe {
+            if let Some((surface_fn, surface)) = surface {
+                surface_fn.destroy_surface(surface, None);
+            }
+            instance.destroy_instance(None);
+        }
+        Ok(VulkanInfo {
+            instance_version: version_from_raw(instance_version),
+            layers,
+            extensions,
+            devices: devices?,
+            surface_error,
+        })
+    }
+
+    /// Everything that the physical device reports, the surface formats and the present modes are
+    /// left empty without a surface. `api_version` is the version of Vulkan used by the instance
+    unsafe fn physical_device_info(
+        instance: &ash::Instance,
+        api_version: u32,
+        physical_device: vk::PhysicalDevice,
+        device: DeviceInfo,
+        surface: Option<(&khr::Surface, vk::SurfaceKHR)>,
+    ) -> PhysicalDeviceInfo {
+        let properties = instance.get_physical_device_properties(physical_device);
+        let memory_properties = instance.get_physical_device_memory_properties(physical_device);
+        let extensions = instance
+            .enumerate_device_extension_properties(physical_device)
+            .unwrap_or_default();
+        let extensions = extensions_info(&extensions);
+
+        // Only the structs of the extensions the device has and of the versions of Vulkan that both
+        // the device and the instance use can be chained
+        let extension_names = extensions
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        let api_version = api_version.min(properties.api_version);
+        let s_types = available_device_features_structs(api_version, &extension_names)
+            .map(|s| s.s_type)
+            .collect::<Vec<_>>();
+        let mut features = DeviceFeaturesChain::new(&s_types).unwrap();
+        instance.get_physical_device_features2(physical_device, features.features2_mut());
+        let features = features
+            .features()
+            .into_iter()
+            .map(|(name, features)| FeatureStructInfo { name, features })
+            .collect();
+
+        let queue_families = instance
+            .get_physical_device_queue_family_properties(physical_device)
+            .iter()
+            .enumerate()
+            .map(|(i, family)| QueueFamilyInfo {
+                flags: format!("{:?}", family.queue_flags),
+                queue_count: family.queue_count,
+                timestamp_valid_bits: family.timestamp_valid_bits,
+                present: surface.map(|(surface_fn, surface)| {
+                    surface_fn
+                        .get_physical_device_surface_support(physical_device, i as u32, surface)
+                        .unwrap_or(false)
+                }),
+            })
+            .collect();
+        let (surface_formats, present_modes) = match surface {
+            Some((surface_fn, surface)) => (
+                surface_fn
+                    .get_physical_device_surface_formats(physical_device, surface)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|f| (format!("{:?}", f.format), format!("{:?}", f.color_space)))
+                    .collect(),
+                surface_fn
+                    .get_physical_device_surface_present_modes(physical_device, surface)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|mode| format!("{:?}", mode))
+                    .collect(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let color_sample_counts = properties.limits.framebuffer_color_sample_counts;
+        PhysicalDeviceInfo {
+            device,
+            api_version: version_from_raw(properties.api_version),
+            driver_version: properties.driver_version,
+            max_image_dimension_2d: properties.limits.max_image_dimension2_d,
+            color_sample_counts: [1, 2, 4, 8, 16, 32, 64]
+                .into_iter()
+                .filter(|count| {
+                    color_sample_counts.contains(vk::SampleCountFlags::from_raw(*count))
+                })
+                .collect(),
+            memory_heaps: memory_properties.memory_heaps
+                [..memory_properties.memory_heap_count as usize]
+                .iter()
+                .map(|heap| MemoryHeapInfo {
+                    size: heap.size,
+                    device_local: heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL),
+                })
+                .collect(),
+            queue_families,
+            surface_formats,
+            present_modes,
+            extensions,
+            features,
+        }
+    }
+
+    /**
+    Checks the layers and the instance extensions that the loader provides and returns the ones to
+    enable: the required extensions, which are an error when missing, followed by the optional
+    layers and extensions that are available, the missing ones are skipped with a warning.
+    */
+    fn select_instance_layers_and_extensions(
+        entry_fn: &ash::Entry,
+        mut extensions: Vec<CString>,
+        optional_layers: &[&str],
+        optional_extensions: &[&str],
+    ) -> Result<(Vec<CString>, Vec<CString>), FPlotError> {
+        let available_layers = entry_fn
+            .enumerate_instance_layer_properties()
+            .map_err(|e| FPlotError::Instance(format!("cannot list the layers: {}", e)))?;
+        let available_layers: HashSet<&CStr> = available_layers
+            .iter()
+            .map(|v| unsafe { CStr::from_ptr(v.layer_name.as_ptr()) })
+            .collect();
+        let available_extensions = entry_fn
+            .enumerate_instance_extension_properties()
+            .map_err(|e| FPlotError::Instance(format!("cannot list the extensions: {}", e)))?;
+        let available_extensions: HashSet<&CStr> = available_extensions
+            .iter()
+            .map(|v| unsafe { CStr::from_ptr(v.extension_name.as_ptr()) })
+            .collect();
+
+        let missing_extensions = extensions
+            .iter()
+            .filter(|e| !available_extensions.contains(e.as_c_str()))
+            .map(|e| e.to_string_lossy())
+            .collect::<Vec<_>>();
+        if !missing_extensions.is_empty() {
+            return Err(FPlotError::Instance(format!(
+                "the Vulkan implementation is missing the instance extensions {}",
+                missing_extensions.join(", ")
+            )));
+        }
+
+        let mut layers = Vec::new();
+        for layer in optional_layers.iter().map(|s| CString::new(*s).unwrap()) {
+            match available_layers.contains(layer.as_c_str()) {
+                true => layers.push(layer),
+                false => eprintln!(
+                    "Warning: the layer {} is not available, it is not enabled",
+                    layer.to_string_lossy()
+                ),
+            }
+        }
+        for extension in optional_extensions
+            .iter()
+            .map(|s| CString::new(*s).unwrap())
+        {
+            match available_extensions.contains(extension.as_c_str()) {
+                true => extensions.push(extension),
+                false => eprintln!(
+                    "Warning: the instance extension {} is not available, it is not enabled",
+                    extension.to_string_lossy()
+                ),
+            }
+        }
+        Ok((layers, extensions))
+    }
+
+    /// The instance extensions needed to create a surface for the window of the handle
+    fn surface_extensions(window_handle: RawWindowHandle) -> Result<[&'static str; 2], FPlotError> {
+        let surface_extension = match window_handle {
+            RawWindowHandle::Win32(_) => "VK_KHR_win32_surface",
+            RawWindowHandle::Xlib(_) => "VK_KHR_xlib_surface",
+            RawWindowHandle::Wayland(_) => "VK_KHR_wayland_surface",
+            _ => {
+                return Err(FPlotError::Surface(
+                    "unsupported kind of window handle".to_string(),
+                ))
+            }
+        };
+        Ok(["VK_KHR_surface", surface_extension])
+    }
+
+    /// Creates the surface of the window, the instance must have the extensions returned by
+    /// `surface_extensions` for its handle
+    unsafe fn create_surface(
+        entry_fn: &ash::Entry,
+        instance: &ash::Instance,
+        window_handle: RawWindowHandle,
+    ) -> Result<vk::SurfaceKHR, vk::Result> {
+        match window_handle {
+            RawWindowHandle::Win32(handle) => {
+                let surface_desc = vk::Win32SurfaceCreateInfoKHR::builder()
+                    .hinstance(handle.hinstance)
+                    .hwnd(handle.hwnd);
+                let win_surface_fn = khr::Win32Surface::new(entry_fn, instance);
+                win_surface_fn.create_win32_surface(&surface_desc, None)
+            }
+            RawWindowHandle::Xlib(handle) => {
+                let surface_desc = vk::XlibSurfaceCreateInfoKHR::builder()
+                    .dpy(handle.display as *mut _)
+                    .window(handle.window);
+                let xlib_surface_fn = khr::XlibSurface::new(entry_fn, instance);
+                xlib_surface_fn.create_xlib_surface(&surface_desc, None)
+            }
+            RawWindowHandle::Wayland(handle) => {
+                let surface_desc = vk::WaylandSurfaceCreateInfoKHR::builder()
+                    .display(handle.display)
+                    .surface(handle.surface);
+                let wayland_surface_fn = khr::WaylandSurface::new(entry_fn, instance);
+                wayland_surface_fn.create_wayland_surface(&surface_desc, None)
+            }
+            // the other handles are rejected by surface_extensions
+            _ => Err(vk::Result::ERROR_EXTENSION_NOT_PRESENT),
+        }
+    }
+
+    fn create_instance(
+        entry_fn: &ash::Entry,
+        application_name: &str,
+        layer_names: &[*const c_char],
+        extension_names: &[*const c_char],
+        api_version: u32,
+    ) -> Result<ash::Instance, FPlotError> {
+        let application_name = CString::new(application_name)
+            .map_err(|_| FPlotError::Instance("invalid application name".to_string()))?;
+        let application_info = vk::ApplicationInfo::builder()
+            .application_name(application_name.as_c_str())
+            .application_version(vk::make_api_version(0, 0, 1, 0))
+            .engine_name(CStr::from_bytes_with_nul(b"TheVulkanTemple\0").unwrap())
+            .engine_version(vk::make_api_version(0, 0, 1, 0))
+            .api_version(api_version);
+        let instance_create_info = vk::InstanceCreateInfo::builder()
+            .application_info(&application_info)
+            .enabled_layer_names(layer_names)
+            .enabled_extension_names(extension_names);
+        unsafe { entry_fn.create_instance(&instance_create_info, None) }
+            .map_err(|e| FPlotError::Instance(e.to_string()))
+    }
+
+    /**
+    Describes each physical device and checks whether it meets the requirements, when a surface is
+    given the device must also be able to present to it. Along with the descriptions it returns
+    the family of the queues that each usable device would use.
+    */
+    unsafe fn evaluate_physical_devices(
+        instance: &ash::Instance,
+        physical_devices: &[vk::PhysicalDevice],
+        desired_device_extensions: &[CString],
+        desired_device_features: &DeviceFeaturesChain,
+        desired_queues: &[(vk::QueueFlags, f32)],
+        surface: Option<(&khr::Surface, vk::SurfaceKHR)>,
+    ) -> (Vec<DeviceInfo>, Vec<u32>) {
+        // Creating a new struct pointer chain to accommodate the features of the physical devices
+        let mut available_device_features = desired_device_features.without_features();
+
+        let mut devices = Vec::new();
+        let mut queue_family_indices = Vec::new();
+        for (index, physical_device) in physical_devices.iter().enumerate() {
+            let properties = instance.get_physical_device_properties(*physical_device);
+            let memory_properties =
+                instance.get_physical_device_memory_properties(*physical_device);
+            let local_memory = memory_properties.memory_heaps
+                [..memory_properties.memory_heap_count as usize]
+                .iter()
+                .filter(|heap| heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL))
+                .map(|heap| heap.size)
+                .sum();
+            let mut info = DeviceInfo {
+                index,
+                name: CStr::from_ptr(properties.device_name.as_ptr())
+                    .to_string_lossy()
+                    .into_owned(),
+                vendor_id: properties.vendor_id,
+                device_id: properties.device_id,
+                device_type: match properties.device_type {
+                    vk::PhysicalDeviceType::DISCRETE_GPU => DeviceType::Discrete,
+                    vk::PhysicalDeviceType::INTEGRATED_GPU => DeviceType::Integrated,
+                    vk::PhysicalDeviceType::VIRTUAL_GPU => DeviceType::Virtual,
+                    vk::PhysicalDeviceType::CPU => DeviceType::Cpu,
+                    _ => DeviceType::Other,
+                },
+                local_memory,
+                queue_count: 0,
+                rejection: None,
+            };
+            let queue_family = Self::check_physical_device(
+                instance,
+                *physical_device,
+                desired_device_extensions,
+                desired_device_features,
+                &mut available_device_features,
+                desired_queues,
+                surface,
+            );
+            match queue_family {
+                Ok((queue_family_index, queue_count)) => {
+                    info.queue_count = queue_count;
+                    queue_family_indices.push(queue_family_index);
+                }
+                Err(reason) => {
+                    info.rejection = Some(reason);
+                    queue_family_indices.push(0);
+                }
+            }
+            devices.push(info);
+        }
+        (devices, queue_family_indices)
+    }
+
+    /// The index and the number of queues of the family the device would use, or why it does not
+    /// meet the requirements
+    unsafe fn check_physical_device(
+        instance: &ash::Instance,
+        physical_device: vk::PhysicalDevice,
+        desired_device_extensions: &[CString],
+        desired_device_features: &DeviceFeaturesChain,
+        available_device_features: &mut DeviceFeaturesChain,
+        desired_queues: &[(vk::QueueFlags, f32)],
+        surface: Option<(&khr::Surface, vk::SurfaceKHR)>,
+    ) -> Result<(u32, u32), String> {
+        // Check if the physical device supports the required extensions
+        let extensions = instance
+            .enumerate_device_extension_properties(physical_device)
+            .map_err(|e| format!("cannot list the extensions: {}", e))?;
+        let extensions_names: HashSet<&CStr> = extensions
+            .iter()
+            .map(|v| CStr::from_ptr(v.extension_name.as_ptr()))
+            .collect();
+        let missing_extensions = desired_device_extensions
+            .iter()
+            .filter(|e| !extensions_names.contains(e.as_c_str()))
+            .map(|e| e.to_string_lossy())
+            .collect::<Vec<_>>();
+        if !missing_extensions.is_empty() {
+            return Err(format!(
+                "missing the extensions {}",
+                missing_extensions.join(", ")
+            ));
+        }
+
+        // Check if the physical device supports the features requested
+        instance.get_physical_device_features2(
+            physical_device,
+            available_device_features.features2_mut(),
+        );
+        // Every device supports robustBufferAccess, even when it does not report it
+        available_device_features.set(
+            vk::StructureType::PHYSICAL_DEVICE_FEATURES_2,
+            "robustBufferAccess",
+            true,
+        )?;
+        if !available_device_features.supports(desired_device_features) {
+            let available = available_device_features.features();
+            let missing_features = desired_device_features
+                .features()
+                .into_iter()
+                .flat_map(|(struct_name, features)| {
+                    let available_features = available
+                        .iter()
+                        .find(|(name, _)| *name == struct_name)
+                        .map(|(_, features)| features);
+                    features
+                        .into_iter()
+                        .filter(move |(feature, enabled)| {
+                            *enabled
+                                && !matches!(available_features, Some(available_features)
+                                    if available_features.contains(&(*feature, true)))
+                        })
+                        .map(move |(feature, _)| format!("{}::{}", struct_name, feature))
+                })
+                .collect::<Vec<_>>();
+            return Err(format!(
+                "missing the features {}",
+                missing_features.join(", ")
+            ));
+        }
+
+        // Check if the physical device supports the requested queues, preferring the family with
+        // the most of them
+        let mut queue_family_properties = Vec::<vk::QueueFamilyProperties2>::new();
+        queue_family_properties.resize(
+            instance.get_physical_device_queue_family_properties2_len(physical_device),
+            vk::QueueFamilyProperties2::default(),
+        );
+        instance.get_physical_device_queue_family_properties2(
+            physical_device,
+            &mut queue_family_properties,
+        );
+        let good_family_queues = queue_family_properties
+            .iter()
+            .map(|queue_family| queue_family.queue_family_properties)
+            .enumerate()
+            .filter(|(_, queue_family)| {
+                desired_queues
+                    .iter()
+                    .all(|q| queue_family.queue_flags.contains(q.0))
+                    && desired_queues.len() <= queue_family.queue_count as usize
+            })
+            .collect::<Vec<_>>();
+        if good_family_queues.is_empty() {
+            return Err("no queue family has the required queues".to_string());
         }
+        good_family_queues
+            .into_iter()
+            .filter(|(i, _)| match surface {
+                Some((surface_fn, surface)) => surface_fn
+                    .get_physical_device_surface_support(physical_device, *i as u32, surface)
+                    .unwrap_or(false),
+                None => true,
+            })
+            .max_by_key(|(_, queue_family)| queue_family.queue_count)
+            .map(|(i, queue_family)| (i as u32, queue_family.queue_count))
+            .ok_or_else(|| "cannot present to the window".to_string())
+    }
+
+    /// Whether the extension, required or optional, has been enabled on the device
+    pub fn is_device_extension_enabled(&self, name: &str) -> bool {
+        self.device_extensions
+            .iter()
+            .any(|extension| extension.to_bytes() == name.as_bytes())
     }
 
     pub fn recreate_swapchain(
@@ -364,7 +946,9 @@ impl BaseVk {
         window_size: vk::Extent2D,
         usage_flags: vk::ImageUsageFlags,
         surface_format: vk::SurfaceFormatKHR,
-    ) {
+    ) -> Result<(), FPlotError> {
+        let surface_fn = self.surface_fn.as_ref().ok_or_else(no_surface_error)?;
+        let swapchain_fn = self.swapchain_fn.as_ref().ok_or_else(no_surface_error)?;
         self.swapchain_create_info = Some(
             vk::SwapchainCreateInfoKHR::builder()
                 .image_array_layers(1)
@@ -376,26 +960,20 @@ impl BaseVk {
                 .build(),
         );
         let swapchain_create_info_ref = self.swapchain_create_info.as_mut().unwrap();
+        let surface_error = |e: vk::Result| FPlotError::Surface(e.to_string());
         let surface_capabilities;
         unsafe {
             // getting the present mode for the swapchain
-            swapchain_create_info_ref.present_mode = *self
-                .surface_fn
-                .as_ref()
-                .expect("BaseVk has not been created with surface support")
-                .borrow()
+            swapchain_create_info_ref.present_mode = *surface_fn
                 .get_physical_device_surface_present_modes(self.physical_device, self.surface)
-                .unwrap()
+                .map_err(surface_error)?
                 .iter()
                 .find(|m| **m == present_mode)
                 .unwrap_or(&vk::PresentModeKHR::FIFO);
 
-            surface_capabilities = self
-                .surface_fn
-                .as_ref()
-                .unwrap()
+            surface_capabilities = surface_fn
                 .get_physical_device_surface_capabilities(self.physical_device, self.surface)
-                .unwrap();
+                .map_err(surface_error)?;
         }
 
         // getting the image count for the swapchain
@@ -431,33 +1009,31 @@ impl BaseVk {
             .supported_usage_flags
             .contains(usage_flags)
         {
-            panic!("Unsupported image usage flags")
+            return Err(FPlotError::Swapchain(format!(
+                "the image usage {:?} is not supported by the surface",
+                usage_flags
+            )));
         }
         swapchain_create_info_ref.image_usage = usage_flags;
 
         // checking if the surface format is supported or a substitute needs to be selected
         unsafe {
-            let supported_formats = self
-                .surface_fn
-                .as_ref()
-                .unwrap()
+            let supported_formats = surface_fn
                 .get_physical_device_surface_formats(self.physical_device, self.surface)
-                .unwrap();
+                .map_err(surface_error)?;
 
             let chosen_format = supported_formats
                 .iter()
                 .find(|e| **e == surface_format)
-                .unwrap_or_else(|| supported_formats.first().unwrap());
+                .or_else(|| supported_formats.first())
+                .ok_or_else(|| FPlotError::Surface("no supported surface format".to_string()))?;
 
             swapchain_create_info_ref.image_format = chosen_format.format;
             swapchain_create_info_ref.image_color_space = chosen_format.color_space;
 
-            self.swapchain = self
-                .swapchain_fn
-                .as_ref()
-                .unwrap()
+            self.swapchain = swapchain_fn
                 .create_swapchain(&self.swapchain_create_info.unwrap(), None)
-                .expect("Could not create swapchain");
+                .map_err(|e| FPlotError::Swapchain(e.to_string()))?;
 
             if let Some(swapchain_image_views) = &mut self.swapchain_image_views {
                 swapchain_image_views
@@ -468,12 +1044,9 @@ impl BaseVk {
                 self.swapchain_image_views = Some(Vec::new());
             }
 
-            let swapchain_images = self
-                .swapchain_fn
-                .as_ref()
-                .unwrap()
+            let swapchain_images = swapchain_fn
                 .get_swapchain_images(self.swapchain)
-                .unwrap();
+                .map_err(|e| FPlotError::Swapchain(e.to_string()))?;
             for swapchain_image in swapchain_images.iter() {
                 let image_view_create_info = vk::ImageViewCreateInfo::builder()
                     .image(*swapchain_image)
@@ -492,36 +1065,42 @@ impl BaseVk {
                 self.swapchain_image_views.as_mut().unwrap().push(
                     self.device
                         .create_image_view(&image_view_create_info, None)
-                        .unwrap(),
+                        .map_err(|e| FPlotError::Swapchain(e.to_string()))?,
                 );
             }
         }
+        Ok(())
     }
 
     pub fn allocate_buffer(
         &mut self,
         buffer_create_info: &vk::BufferCreateInfo,
         memory_location: MemoryLocation,
-    ) -> BufferAllocation {
-        let buffer = unsafe { self.device.create_buffer(buffer_create_info, None) }.unwrap();
+    ) -> Result<BufferAllocation, FPlotError> {
+        let buffer = unsafe { self.device.create_buffer(buffer_create_info, None) }
+            .map_err(|e| FPlotError::Allocation(e.to_string()))?;
         let requirements = unsafe { self.device.get_buffer_memory_requirements(buffer) };
 
-        let allocation = self
-            .allocator
-            .allocate(&vkalloc::AllocationCreateDesc {
-                name: "",
-                requirements,
-                location: memory_location,
-                linear: true, // buffers are always linear
-            })
-            .unwrap();
+        let allocation = self.allocator.allocate(&vkalloc::AllocationCreateDesc {
+            name: "",
+            requirements,
+            location: memory_location,
+            linear: true, // buffers are always linear
+        });
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(e) => {
+                unsafe { self.device.destroy_buffer(buffer, None) };
+                return Err(FPlotError::Allocation(e.to_string()));
+            }
+        };
 
         unsafe {
             self.device
                 .bind_buffer_memory(buffer, allocation.memory(), allocation.offset())
-                .unwrap()
+                .map_err(|e| FPlotError::Allocation(e.to_string()))?
         };
-        BufferAllocation { buffer, allocation }
+        Ok(BufferAllocation { buffer, allocation })
     }
 
     pub fn destroy_buffer(&mut self, buffer: &BufferAllocation) {
@@ -529,31 +1108,90 @@ impl BaseVk {
         unsafe { self.device.destroy_buffer(buffer.buffer, None) };
     }
 
+    pub fn allocate_image(
+        &mut self,
+        image_create_info: &vk::ImageCreateInfo,
+        memory_location: MemoryLocation,
+    ) -> Result<ImageAllocation, FPlotError> {
+        let image = unsafe { self.device.create_image(image_create_info, None) }
+            .map_err(|e| FPlotError::Allocation(e.to_string()))?;
+        let requirements = unsafe { self.device.get_image_memory_requirements(image) };
+
+        let allocation = self.allocator.allocate(&vkalloc::AllocationCreateDesc {
+            name: "",
+            requirements,
+            location: memory_location,
+            linear: image_create_info.tiling == vk::ImageTiling::LINEAR,
+        });
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(e) => {
+                unsafe { self.device.destroy_image(image, None) };
+                return Err(FPlotError::Allocation(e.to_string()));
+            }
+        };
+
+        unsafe {
+            self.device
+                .bind_image_memory(image, allocation.memory(), allocation.offset())
+                .map_err(|e| FPlotError::Allocation(e.to_string()))?
+        };
+        Ok(ImageAllocation { image, allocation })
+    }
+
+    pub fn destroy_image(&mut self, image: &ImageAllocation) {
+        self.allocator.free(image.allocation.clone()).unwrap();
+        unsafe { self.device.destroy_image(image.image, None) };
+    }
+
+    pub fn get_physical_device_properties(&self) -> vk::PhysicalDeviceProperties {
+        unsafe {
+            self.instance
+                .get_physical_device_properties(self.physical_device)
+        }
+    }
+
+    /// Usage flags that the images of a swapchain for the surface of the window can have
+    pub fn get_surface_supported_usage_flags(&self) -> Result<vk::ImageUsageFlags, FPlotError> {
+        unsafe {
+            self.surface_fn
+                .as_ref()
+                .ok_or_else(no_surface_error)?
+                .get_physical_device_surface_capabilities(self.physical_device, self.surface)
+                .map(|capabilities| capabilities.supported_usage_flags)
+                .map_err(|e| FPlotError::Surface(e.to_string()))
+        }
+    }
+
     pub fn create_cmd_pool_and_buffers(
         &mut self,
         pool_flags: vk::CommandPoolCreateFlags,
         cmdb_level: vk::CommandBufferLevel,
         cmdb_count: u32,
-    ) -> CommandRecordInfo {
+    ) -> Result<CommandRecordInfo, FPlotError> {
         let command_pool_create_info = vk::CommandPoolCreateInfo::builder()
             .flags(pool_flags)
             .queue_family_index(self.queue_family_index);
         let pool = unsafe {
             self.device
                 .create_command_pool(&command_pool_create_info, None)
-                .unwrap()
-        };
+        }
+        .map_err(|e| device_error("cannot create a command pool", e))?;
 
         let command_buffers_allocate_info = vk::CommandBufferAllocateInfo::builder()
             .command_pool(pool)
             .level(cmdb_level)
             .command_buffer_count(cmdb_count);
-        let buffers = unsafe {
+        match unsafe {
             self.device
                 .allocate_command_buffers(&command_buffers_allocate_info)
-                .unwrap()
-        };
-        CommandRecordInfo { pool, buffers }
+        } {
+            Ok(buffers) => Ok(CommandRecordInfo { pool, buffers }),
+            Err(e) => {
+                unsafe { self.device.destroy_command_pool(pool, None) };
+                Err(device_error("cannot allocate the command buffers", e))
+            }
+        }
     }
 
     pub fn destroy_cmd_pool_and_buffers(&mut self, cmri: &CommandRecordInfo) {
@@ -567,26 +1205,30 @@ impl BaseVk {
         &mut self,
         pool_sizes: &[vk::DescriptorPoolSize],
         sets: &[vk::DescriptorSetLayout],
-    ) -> DescriptorInfo {
+    ) -> Result<DescriptorInfo, FPlotError> {
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo::builder()
             .max_sets(sets.len() as u32)
             .pool_sizes(&pool_sizes);
         let descriptor_pool = unsafe {
             self.device
                 .create_descriptor_pool(&descriptor_pool_create_info, None)
-                .unwrap()
-        };
+        }
+        .map_err(|e| device_error("cannot create a descriptor pool", e))?;
         let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo::builder()
             .descriptor_pool(descriptor_pool)
             .set_layouts(sets);
-        let descriptor_sets = unsafe {
+        match unsafe {
             self.device
                 .allocate_descriptor_sets(&descriptor_set_allocate_info)
-                .unwrap()
-        };
-        DescriptorInfo {
-            pool: descriptor_pool,
-            buffers: descriptor_sets,
+        } {
+            Ok(descriptor_sets) => Ok(DescriptorInfo {
+                pool: descriptor_pool,
+                buffers: descriptor_sets,
+            }),
+            Err(e) => {
+                unsafe { self.device.destroy_descriptor_pool(descriptor_pool, None) };
+                Err(device_error("cannot allocate the descriptor sets", e))
+            }
         }
     }
 
@@ -596,15 +1238,19 @@ impl BaseVk {
         }
     }
 
-    pub fn create_semaphores(&mut self, count: u32) -> Vec<vk::Semaphore> {
+    pub fn create_semaphores(&mut self, count: u32) -> Result<Vec<vk::Semaphore>, FPlotError> {
         let semaphore_create_info = vk::SemaphoreCreateInfo::builder();
-        (0..count)
-            .map(|_| unsafe {
-                self.device
-                    .create_semaphore(&semaphore_create_info, None)
-                    .unwrap()
-            })
-            .collect()
+        let mut semaphores = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            match unsafe { self.device.create_semaphore(&semaphore_create_info, None) } {
+                Ok(semaphore) => semaphores.push(semaphore),
+                Err(e) => {
+                    self.destroy_semaphores(&semaphores);
+                    return Err(device_error("cannot create a semaphore", e));
+                }
+            }
+        }
+        Ok(semaphores)
     }
 
     pub fn destroy_semaphores(&mut self, semaphores: &Vec<vk::Semaphore>) {
@@ -632,9 +1278,46 @@ impl Drop for BaseVk {
                 fp.destroy_surface(self.surface, None);
             }
             #[cfg(debug_assertions)]
-            self.debug_utils_fn
-                .destroy_debug_utils_messenger(self.debug_utils_messenger, None);
+            if self.debug_utils_messenger != vk::DebugUtilsMessengerEXT::null() {
+                self.debug_utils_fn
+                    .destroy_debug_utils_messenger(self.debug_utils_messenger, None);
+            }
             self.instance.destroy_instance(None);
         }
     }
 }
+
+/// Error of the operations on the window surface of a BaseVk created without one
+fn no_surface_error() -> FPlotError {
+    FPlotError::Surface("the renderer has been created without a window".to_string())
+}
+
+/// The error of a Vulkan call on the device, `what` tells what could not be done
+pub fn device_error(what: &str, e: vk::Result) -> FPlotError {
+    FPlotError::Device(format!("{}: {}", what, e))
+}
+
+/// The string of a null terminated array of chars, like the names in the Vulkan properties
+unsafe fn c_chars_to_string(chars: &[c_char]) -> String {
+    CStr::from_ptr(chars.as_ptr())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn version_from_raw(version: u32) -> Version {
+    Version {
+        major: vk::api_version_major(version),
+        minor: vk::api_version_minor(version),
+        patch: vk::api_version_patch(version),
+    }
+}
+
+fn extensions_info(extensions: &[vk::ExtensionProperties]) -> Vec<ExtensionInfo> {
+    extensions
+        .iter()
+        .map(|extension| ExtensionInfo {
+            name: unsafe { c_chars_to_string(&extension.extension_name) },
+            spec_version: extension.spec_version,
+        })
+        .collect()
+}
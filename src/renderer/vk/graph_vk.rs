@@ -1,4 +1,14 @@
 use super::base_vk::*;
+use super::pointer_chain_helpers::DeviceFeaturesChain;
+use crate::error::FPlotError;
+use crate::font;
+use crate::grid::{self, PageLabel, LABEL_FONT_SIZE};
+use crate::renderer::device::{DeviceInfo, DeviceSelector};
+use crate::renderer::present_mode::PresentMode;
+use crate::renderer::style::{DashPattern, LineStyle};
+use crate::renderer::vulkan_info::VulkanInfo;
+use crate::renderer::window_manager::WindowManager;
+use crate::theme::{Color, Theme};
 use ash::{extensions::*, vk};
 use gpu_allocator::MemoryLocation;
 use nalgebra::*;
@@ -6,6 +16,95 @@ use raw_window_handle::RawWindowHandle;
 use std::ffi::CStr;
 use std::mem::size_of;
 
+// Layout of the vertex buffer: the two axes, the selection rectangle, the grid lines of the x
+// axis, the ones of the y axis, the strokes of their labels and then the curves, one after the
+// other with a point for each column of pixels. The labels that do not fit are not drawn
+const AXES_VERTICES: usize = 4;
+const SELECTION_VERTICES: usize = 5;
+const GRID_FIRST_VERTEX: usize = AXES_VERTICES + SELECTION_VERTICES;
+const GRID_VERTICES: usize = 2 * 2 * grid::MAX_LINES;
+const LABEL_FIRST_VERTEX: usize = GRID_FIRST_VERTEX + GRID_VERTICES;
+const LABEL_VERTICES: usize = 128 * grid::MAX_LINES;
+const CURVE_FIRST_VERTEX: usize = LABEL_FIRST_VERTEX + LABEL_VERTICES;
+
+// Each point of the lines is stored as its world coordinates followed by its distance in pixels
+// from the first point of the line, which is used to place the dashes
+type Point = [f32; 3];
+
+// Used when available, otherwise the barriers and the submits of Vulkan 1.0 are used
+const SYNCHRONIZATION_2_EXTENSION: &str = "VK_KHR_synchronization2";
+
+// Vertices of the triangle strip that covers a segment of a line
+const SEGMENT_VERTICES: u32 = 4;
+
+/// Layout of the push constants shared by the vertex and fragment shaders
+#[repr(C)]
+struct LinePushConstants {
+    color: [f32; 4],
+    width: f32,
+    dash_length: f32,
+    gap_length: f32,
+}
+
+// Format of the off-screen image, whose pixels are read back as RGBA
+const OFFSCREEN_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const OFFSCREEN_USAGE: vk::ImageUsageFlags = vk::ImageUsageFlags::from_raw(
+    vk::ImageUsageFlags::COLOR_ATTACHMENT.as_raw() | vk::ImageUsageFlags::TRANSFER_SRC.as_raw(),
+);
+
+/// Image drawn by a headless renderer or for a screenshot, copied to a host visible buffer
+struct OffscreenTarget {
+    image: ImageAllocation,
+    image_view: vk::ImageView,
+    readback_buffer: BufferAllocation,
+    extent: vk::Extent2D,
+}
+
+/// The images the plot is drawn to, the ones of the window swapchain or a single off-screen one
+enum RenderTarget {
+    Swapchain,
+    Offscreen(OffscreenTarget),
+}
+
+impl RenderTarget {
+    fn extent(&self, bvk: &BaseVk) -> vk::Extent2D {
+        match self {
+            RenderTarget::Swapchain => bvk.swapchain_create_info.unwrap().image_extent,
+            RenderTarget::Offscreen(target) => target.extent,
+        }
+    }
+
+    fn format(&self, bvk: &BaseVk) -> vk::Format {
+        match self {
+            RenderTarget::Swapchain => bvk.swapchain_create_info.unwrap().image_format,
+            RenderTarget::Offscreen(_) => OFFSCREEN_FORMAT,
+        }
+    }
+
+    fn usage(&self, bvk: &BaseVk) -> vk::ImageUsageFlags {
+        match self {
+            RenderTarget::Swapchain => bvk.swapchain_create_info.unwrap().image_usage,
+            RenderTarget::Offscreen(_) => OFFSCREEN_USAGE,
+        }
+    }
+
+    fn image_views(&self, bvk: &BaseVk) -> Vec<vk::ImageView> {
+        match self {
+            RenderTarget::Swapchain => bvk.swapchain_image_views.clone().unwrap(),
+            RenderTarget::Offscreen(target) => vec![target.image_view],
+        }
+    }
+
+    /// Layout of the image at the end of the render pass, the off-screen one is then transitioned
+    /// for the copy to the readback buffer
+    fn final_layout(&self) -> vk::ImageLayout {
+        match self {
+            RenderTarget::Swapchain => vk::ImageLayout::PRESENT_SRC_KHR,
+            RenderTarget::Offscreen(_) => vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        }
+    }
+}
+
 struct FrameData {
     after_exec_fence: vk::Fence,
     main_command: CommandRecordInfo,
@@ -13,7 +112,10 @@ struct FrameData {
 
 pub struct GraphVk {
     bvk: BaseVk,
-    sync2: khr::Synchronization2,
+    target: RenderTarget,
+    // None when the device does not support synchronization2, then the barriers and the submits
+    // of Vulkan 1.0 are used instead
+    sync2: Option<khr::Synchronization2>,
     host_curve_buffer: BufferAllocation,
     device_curve_buffer: BufferAllocation,
     transform_uniform_buffer: BufferAllocation,
@@ -26,142 +128,440 @@ pub struct GraphVk {
     framebuffer: vk::Framebuffer,
     semaphores: Vec<vk::Semaphore>,
     frames_count: u64,
+    selection: Option<[Vector2<f32>; 2]>,
+    curves_visible: Vec<bool>,
+    curve_styles: Vec<LineStyle>,
+    // Colors that replace the ones of the theme
+    curve_colors: Vec<Option<Color>>,
+    theme: Theme,
+    grid_visible: bool,
+    samples: vk::SampleCountFlags,
+    multisampled_target: Option<(ImageAllocation, vk::ImageView)>,
 }
 
 impl GraphVk {
-    pub fn new(window_size: (u32, u32), window_handle: RawWindowHandle) -> Self {
-        let mut imageless_fb = vk::PhysicalDeviceImagelessFramebufferFeatures::builder()
-            .imageless_framebuffer(true);
-        let mut sync2 =
-            vk::PhysicalDeviceSynchronization2FeaturesKHR::builder().synchronization2(true);
-        let mut desired_features = vk::PhysicalDeviceFeatures2::builder()
-            .push_next(&mut sync2)
-            .push_next(&mut imageless_fb);
-        desired_features.features.fill_mode_non_solid = vk::TRUE;
-        let mut base_vk = BaseVk::new(
-            "FPlot",
-            &[],
-            &["VK_KHR_synchronization2", "VK_KHR_imageless_framebuffer", "VK_KHR_image_format_list"],
-            &desired_features,
-            std::slice::from_ref(&(vk::QueueFlags::GRAPHICS, 1.0f32)),
-            Some(window_handle),
-        );
+    /// Creates the renderer for the given window, `msaa_samples` is the requested number of
+    /// samples per pixel which is lowered to the highest one supported by the device
+    pub fn new(
+        window_size: (u32, u32),
+        window_handle: RawWindowHandle,
+        msaa_samples: u32,
+        present_mode: PresentMode,
+        device: Option<&DeviceSelector>,
+    ) -> Result<Self, FPlotError> {
+        let (mut base_vk, synchronization2) = Self::create_base_vk(Some(window_handle), device)?;
+        // screenshots copy the swapchain images directly when they can be a transfer source
+        let usage = vk::ImageUsageFlags::COLOR_ATTACHMENT
+            | (base_vk.get_surface_supported_usage_flags()? & vk::ImageUsageFlags::TRANSFER_SRC);
+        let present_mode = match present_mode {
+            PresentMode::Fifo => vk::PresentModeKHR::FIFO,
+            PresentMode::FifoRelaxed => vk::PresentModeKHR::FIFO_RELAXED,
+            PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX,
+            PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
+        };
         base_vk.recreate_swapchain(
-            vk::PresentModeKHR::MAILBOX,
+            present_mode,
             vk::Extent2D {
                 width: window_size.0,
                 height: window_size.1,
             },
-            vk::ImageUsageFlags::COLOR_ATTACHMENT,
+            usage,
             vk::SurfaceFormatKHR {
                 format: vk::Format::B8G8R8_UNORM,
                 color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
             },
-        );
-        let sync2 = khr::Synchronization2::new(&base_vk.instance, &base_vk.device);
-        let buffers = Self::create_curve_vertex_buffers(&mut base_vk, window_size.0 as usize);
+        )?;
+        Self::with_target(
+            base_vk,
+            RenderTarget::Swapchain,
+            msaa_samples,
+            synchronization2,
+        )
+    }
+
+    /// Creates a renderer that draws to an off-screen image of the given size instead of a
+    /// window, the frames are read with `render_offscreen`
+    pub fn new_headless(
+        size: (u32, u32),
+        msaa_samples: u32,
+        device: Option<&DeviceSelector>,
+    ) -> Result<Self, FPlotError> {
+        let (mut base_vk, synchronization2) = Self::create_base_vk(None, device)?;
+        let target = Self::create_offscreen_target(
+            &mut base_vk,
+            OFFSCREEN_FORMAT,
+            vk::Extent2D {
+                width: size.0,
+                height: size.1,
+            },
+        )?;
+        Self::with_target(
+            base_vk,
+            RenderTarget::Offscreen(target),
+            msaa_samples,
+            synchronization2,
+        )
+    }
+
+    /// Creates the instance and the device, which is the given one, the one of the `FPLOT_DEVICE`
+    /// environment variable or else the best ranked one, and tells whether synchronization2 has
+    /// been enabled on it
+    fn create_base_vk(
+        window_handle: Option<RawWindowHandle>,
+        device: Option<&DeviceSelector>,
+    ) -> Result<(BaseVk, bool), FPlotError> {
+        let env_device = device.is_none().then(DeviceSelector::from_env).flatten();
+        let (base_vk, synchronization2) = Self::with_device_requirements(|requirements| {
+            BaseVk::new(
+                "FPlot",
+                &[],
+                requirements,
+                window_handle,
+                device.or(env_device.as_ref()),
+            )
+        })?;
+        let base_vk = base_vk?;
+        let synchronization2 =
+            synchronization2 && base_vk.is_device_extension_enabled(SYNCHRONIZATION_2_EXTENSION);
+        Ok((base_vk, synchronization2))
+    }
+
+    /// Describes the devices found and why the ones that cannot draw the plots are rejected, the
+    /// support for presenting to a window is not checked
+    pub fn list_devices() -> Result<Vec<DeviceInfo>, FPlotError> {
+        Self::with_device_requirements(|requirements| BaseVk::list_devices("FPlot", &requirements))?
+            .0
+    }
+
+    /**
+    Reports the Vulkan implementation and what each device supports. The surfaces are queried with
+    a hidden window, when it cannot be created the report tells why their formats and present
+    modes are missing.
+    */
+    pub fn vulkan_info() -> Result<VulkanInfo, FPlotError> {
+        let window = WindowManager::new_hidden();
+        let window_handle = window.as_ref().ok().map(WindowManager::get_window_handle);
+        let info = Self::with_device_requirements(|requirements| {
+            BaseVk::vulkan_info("FPlot", &requirements, window_handle)
+        })?
+        .0;
+        info.map(|mut info| {
+            if let Err(e) = window {
+                info.surface_error = Some(e.to_string());
+            }
+            info
+        })
+    }
+
+    /**
+    Calls `f` with the extensions, the features and the queues that the device must support and the
+    ones that are used when available. Returns the result of `f` and whether the synchronization2
+    feature is left enabled in the optional features, which means that the device supports it.
+    */
+    fn with_device_requirements<R>(
+        f: impl FnOnce(DeviceRequirements) -> R,
+    ) -> Result<(R, bool), FPlotError> {
+        let sync2 = vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES_KHR;
+        let mut desired_features = DeviceFeaturesChain::default();
+        desired_features
+            .set(
+                vk::StructureType::PHYSICAL_DEVICE_IMAGELESS_FRAMEBUFFER_FEATURES,
+                "imagelessFramebuffer",
+                true,
+            )
+            .map_err(FPlotError::DeviceSelection)?;
+        let mut optional_features = DeviceFeaturesChain::default();
+        optional_features
+            .set(sync2, "synchronization2", true)
+            .map_err(FPlotError::DeviceSelection)?;
+        // Lines are drawn as triangles, so neither wide lines nor non solid fill modes are needed
+        let result = f(DeviceRequirements {
+            extensions: &["VK_KHR_imageless_framebuffer", "VK_KHR_image_format_list"],
+            optional_extensions: &[SYNCHRONIZATION_2_EXTENSION],
+            features: &desired_features,
+            optional_features: &mut optional_features,
+            queues: std::slice::from_ref(&(vk::QueueFlags::GRAPHICS, 1.0f32)),
+        });
+        let sync2_enabled = optional_features.get(sync2, "synchronization2") == Some(true);
+        Ok((result, sync2_enabled))
+    }
+
+    /**
+    Creates the objects that draw to the target. The buffers are destroyed here when a later one
+    cannot be created, while the other objects are created once the renderer exists: they start as
+    null handles, which Drop ignores, so a failure destroys the ones created before along with it.
+    */
+    fn with_target(
+        mut base_vk: BaseVk,
+        target: RenderTarget,
+        msaa_samples: u32,
+        synchronization2: bool,
+    ) -> Result<Self, FPlotError> {
+        let extent = target.extent(&base_vk);
+        let format = target.format(&base_vk);
+        let sync2 = synchronization2
+            .then(|| khr::Synchronization2::new(&base_vk.instance, &base_vk.device));
+        let buffers = match Self::create_curve_vertex_buffers(&mut base_vk, extent.width as usize) {
+            Ok(buffers) => buffers,
+            Err(e) => {
+                if let RenderTarget::Offscreen(target) = &target {
+                    Self::destroy_offscreen_target(&mut base_vk, target);
+                }
+                return Err(e);
+            }
+        };
 
+        // The transform is followed by the size of the viewport
         let buffer_create_info = vk::BufferCreateInfo::builder()
-            .size(std::mem::size_of::<nalgebra::Matrix4<f32>>() as u64)
+            .size((size_of::<Matrix4<f32>>() + size_of::<Vector2<f32>>()) as u64)
             .usage(vk::BufferUsageFlags::UNIFORM_BUFFER);
         let transform_uniform_buffer =
-            base_vk.allocate_buffer(&buffer_create_info, MemoryLocation::CpuToGpu);
+            match base_vk.allocate_buffer(&buffer_create_info, MemoryLocation::CpuToGpu) {
+                Ok(buffer) => buffer,
+                Err(e) => {
+                    buffers
+                        .iter()
+                        .for_each(|buffer| base_vk.destroy_buffer(buffer));
+                    if let RenderTarget::Offscreen(target) = &target {
+                        Self::destroy_offscreen_target(&mut base_vk, target);
+                    }
+                    return Err(e);
+                }
+            };
 
-        let renderpass = Self::create_renderpass(&mut base_vk);
+        let samples = Self::select_sample_count(&base_vk, msaa_samples);
+        let mut graph_vk = GraphVk {
+            bvk: base_vk,
+            target,
+            sync2,
+            host_curve_buffer: buffers[0].clone(),
+            device_curve_buffer: buffers[1].clone(),
+            transform_uniform_buffer,
+            frames_data: Vec::new(),
+            renderpass: vk::RenderPass::null(),
+            descriptor_set_layout: vk::DescriptorSetLayout::null(),
+            pipeline_layout: vk::PipelineLayout::null(),
+            pipeline: vk::Pipeline::null(),
+            descriptor_info: DescriptorInfo {
+                pool: vk::DescriptorPool::null(),
+                buffers: Vec::new(),
+            },
+            framebuffer: vk::Framebuffer::null(),
+            semaphores: Vec::new(),
+            frames_count: 0,
+            selection: None,
+            curves_visible: vec![true],
+            curve_styles: vec![LineStyle::default()],
+            curve_colors: vec![None],
+            theme: Theme::default(),
+            grid_visible: true,
+            samples,
+            multisampled_target: None,
+        };
+        let bvk = &mut graph_vk.bvk;
+        graph_vk.multisampled_target =
+            Self::create_multisampled_target(bvk, format, extent, samples)?;
+        let final_layout = graph_vk.target.final_layout();
+        graph_vk.renderpass = Self::create_renderpass(bvk, format, final_layout, samples)?;
         let (descriptor_set_layout, descriptor_pool_size) =
-            Self::create_descriptor_set_layout(&mut base_vk);
-        let pipeline_data = Self::create_graph_pipeline(
-            &mut base_vk,
+            Self::create_descriptor_set_layout(bvk)?;
+        graph_vk.descriptor_set_layout = descriptor_set_layout;
+        let (pipeline_layout, pipeline) = Self::create_graph_pipeline(
+            bvk,
             std::path::Path::new("assets/shaders-spirv"),
-            renderpass,
+            graph_vk.renderpass,
             descriptor_set_layout,
-        );
-        let descriptor_info = base_vk.create_descriptor_pool_and_sets(
+            samples,
+        )?;
+        graph_vk.pipeline_layout = pipeline_layout;
+        graph_vk.pipeline = pipeline;
+        graph_vk.descriptor_info = bvk.create_descriptor_pool_and_sets(
             std::slice::from_ref(&descriptor_pool_size),
             std::slice::from_ref(&descriptor_set_layout),
-        );
-        let framebuffer = Self::create_framebuffer(&mut base_vk, renderpass);
-        let semaphores = base_vk.create_semaphores(2);
+        )?;
+        graph_vk.framebuffer = Self::create_framebuffer(
+            bvk,
+            graph_vk.renderpass,
+            graph_vk.target.usage(bvk),
+            format,
+            extent,
+            samples,
+        )?;
+        graph_vk.semaphores = bvk.create_semaphores(2)?;
 
         let fence_create_info =
             vk::FenceCreateInfo::builder().flags(vk::FenceCreateFlags::SIGNALED);
-        let frames_data = (0..3)
-            .map(|_| FrameData {
-                after_exec_fence: unsafe {
-                    base_vk
-                        .device
-                        .create_fence(&fence_create_info, None)
-                        .unwrap()
-                },
-                main_command: base_vk.create_cmd_pool_and_buffers(
-                    vk::CommandPoolCreateFlags::empty(),
-                    vk::CommandBufferLevel::PRIMARY,
-                    base_vk.swapchain_image_views.as_ref().unwrap().len() as u32,
-                ),
-            })
-            .collect();
-        GraphVk {
-            bvk: base_vk,
-            sync2,
-            host_curve_buffer: buffers[0].clone(),
-            device_curve_buffer: buffers[1].clone(),
-            transform_uniform_buffer,
-            frames_data,
-            renderpass,
-            descriptor_set_layout,
-            pipeline_layout: pipeline_data.0,
-            pipeline: pipeline_data.1,
-            descriptor_info,
-            framebuffer,
-            semaphores,
-            frames_count: 0,
+        let image_count = graph_vk.target.image_views(bvk).len() as u32;
+        for _ in 0..3 {
+            let after_exec_fence = unsafe { bvk.device.create_fence(&fence_create_info, None) }
+                .map_err(|e| device_error("cannot create a fence", e))?;
+            let main_command = match bvk.create_cmd_pool_and_buffers(
+                vk::CommandPoolCreateFlags::empty(),
+                vk::CommandBufferLevel::PRIMARY,
+                image_count,
+            ) {
+                Ok(main_command) => main_command,
+                Err(e) => {
+                    unsafe { bvk.device.destroy_fence(after_exec_fence, None) };
+                    return Err(e);
+                }
+            };
+            graph_vk.frames_data.push(FrameData {
+                after_exec_fence,
+                main_command,
+            });
         }
+        Ok(graph_vk)
     }
 
-    fn create_curve_vertex_buffers(bvk: &mut BaseVk, points: usize) -> [BufferAllocation; 2] {
-        // The size required for the buffers is calculated as the size of the points and the axes
+    fn create_offscreen_target(
+        bvk: &mut BaseVk,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> Result<OffscreenTarget, FPlotError> {
+        let image_create_info = vk::ImageCreateInfo::builder()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(format)
+            .extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(OFFSCREEN_USAGE)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        let image = bvk.allocate_image(&image_create_info, MemoryLocation::GpuOnly)?;
+        let image_view = match Self::create_color_image_view(bvk, image.image, format) {
+            Ok(image_view) => image_view,
+            Err(e) => {
+                bvk.destroy_image(&image);
+                return Err(e);
+            }
+        };
+        let readback_buffer = match Self::create_readback_buffer(bvk, extent) {
+            Ok(readback_buffer) => readback_buffer,
+            Err(e) => {
+                unsafe { bvk.device.destroy_image_view(image_view, None) };
+                bvk.destroy_image(&image);
+                return Err(e);
+            }
+        };
+        Ok(OffscreenTarget {
+            image,
+            image_view,
+            readback_buffer,
+            extent,
+        })
+    }
+
+    fn destroy_offscreen_target(bvk: &mut BaseVk, target: &OffscreenTarget) {
+        unsafe { bvk.device.destroy_image_view(target.image_view, None) };
+        bvk.destroy_image(&target.image);
+        bvk.destroy_buffer(&target.readback_buffer);
+    }
+
+    /// Creates a host visible buffer that can hold an image of the given extent with 4 bytes
+    /// per pixel
+    fn create_readback_buffer(
+        bvk: &mut BaseVk,
+        extent: vk::Extent2D,
+    ) -> Result<BufferAllocation, FPlotError> {
+        let buffer_create_info = vk::BufferCreateInfo::builder()
+            .size(extent.width as u64 * extent.height as u64 * 4)
+            .usage(vk::BufferUsageFlags::TRANSFER_DST)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE);
+        bvk.allocate_buffer(&buffer_create_info, MemoryLocation::GpuToCpu)
+    }
+
+    fn create_color_image_view(
+        bvk: &BaseVk,
+        image: vk::Image,
+        format: vk::Format,
+    ) -> Result<vk::ImageView, FPlotError> {
+        let image_view_create_info = vk::ImageViewCreateInfo::builder()
+            .image(image)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(format)
+            .components(vk::ComponentMapping::default())
+            .subresource_range(
+                vk::ImageSubresourceRange::builder()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .base_mip_level(0)
+                    .level_count(1)
+                    .base_array_layer(0)
+                    .layer_count(1)
+                    .build(),
+            );
+        unsafe { bvk.device.create_image_view(&image_view_create_info, None) }
+            .map_err(|e| device_error("cannot create an image view", e))
+    }
+
+    fn create_curve_vertex_buffers(
+        bvk: &mut BaseVk,
+        points: usize,
+    ) -> Result<[BufferAllocation; 2], FPlotError> {
+        // The size required for the buffers is calculated as the size of the points of the curves,
+        // the axes, the selection rectangle, the grid and its labels
         let mut buffer_create_info = vk::BufferCreateInfo::builder()
-            .size(((points + 4) * (2 * std::mem::size_of::<f32>())) as u64)
+            .size(((points + CURVE_FIRST_VERTEX) * size_of::<Point>()) as u64)
             .usage(vk::BufferUsageFlags::TRANSFER_SRC)
             .sharing_mode(vk::SharingMode::EXCLUSIVE)
             .build();
         let host_buffer =
-            bvk.allocate_buffer(&buffer_create_info, gpu_allocator::MemoryLocation::GpuToCpu);
+            bvk.allocate_buffer(&buffer_create_info, gpu_allocator::MemoryLocation::GpuToCpu)?;
 
         buffer_create_info.usage =
             vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST;
-        let device_buffer =
-            bvk.allocate_buffer(&buffer_create_info, gpu_allocator::MemoryLocation::GpuOnly);
-        [host_buffer, device_buffer]
+        match bvk.allocate_buffer(&buffer_create_info, gpu_allocator::MemoryLocation::GpuOnly) {
+            Ok(device_buffer) => Ok([host_buffer, device_buffer]),
+            Err(e) => {
+                bvk.destroy_buffer(&host_buffer);
+                Err(e)
+            }
+        }
     }
 
-    fn recreate_curve_vertex_buffers(&mut self, points: usize) {
-        let size = (points + 4) * (2 * std::mem::size_of::<f32>());
+    /// Enlarges the vertex buffers if they cannot hold the given number of points for each curve
+    fn recreate_curve_vertex_buffers(&mut self, points_per_curve: usize) -> Result<(), FPlotError> {
+        let points = points_per_curve * self.curves_visible.len();
+        let size = (points + CURVE_FIRST_VERTEX) * size_of::<Point>();
         if size > self.host_curve_buffer.allocation.size() as usize {
             self.bvk.destroy_buffer(&self.host_curve_buffer);
             self.bvk.destroy_buffer(&self.device_curve_buffer);
 
-            let v = Self::create_curve_vertex_buffers(&mut self.bvk, points);
+            let v = Self::create_curve_vertex_buffers(&mut self.bvk, points)?;
             self.host_curve_buffer = v[0].clone();
             self.device_curve_buffer = v[1].clone();
         }
+        Ok(())
     }
 
-    pub fn fill_graph_buffer(&mut self, x_start: f32, x_end: f32, fun: fn(f32) -> f32) {
-        let step = ((x_end - x_start).abs())
-            / self.bvk.swapchain_create_info.unwrap().image_extent.width as f32;
-        let mut x = x_start;
-        let data_slice = unsafe {
+    /// The points in the host visible vertex buffer
+    fn points_mut(&mut self) -> &mut [Point] {
+        unsafe {
             std::slice::from_raw_parts_mut(
                 self
                     .host_curve_buffer
                     .allocation
                     .mapped_ptr()
                     .unwrap()
-                    .as_ptr() as *mut [f32; 2],
-                self.host_curve_buffer.allocation.size() as usize / std::mem::size_of::<[f32; 2]>(),
+                    .as_ptr() as *mut Point,
+                self.host_curve_buffer.allocation.size() as usize / size_of::<Point>(),
             )
-        };
+        }
+    }
+
+    /// Samples the curves once per column of pixels between `x_start` and `x_end`, the N-th
+    /// function gives the points of the N-th curve and the extra ones are ignored
+    pub fn fill_graph_buffer<F: Fn(f32) -> f32>(&mut self, x_start: f32, x_end: f32, funs: &[F]) {
+        let points = self.target.extent(&self.bvk).width as usize;
+        let curves = self.curves_visible.len();
+        let step = ((x_end - x_start).abs()) / points as f32;
+        let data_slice = self.points_mut();
 
         // points of the x axis
         data_slice[0][0] = x_start;
@@ -169,77 +569,370 @@ impl GraphVk {
         data_slice[1][0] = x_end;
         data_slice[1][1] = 0.0f32;
 
-        for i in 4..(self.bvk.swapchain_create_info.unwrap().image_extent.width + 4) as usize {
-            data_slice[i][0] = x;
-            data_slice[i][1] = fun(x);
-            x += step;
+        let curves_end = CURVE_FIRST_VERTEX + curves * points;
+        for (curve_slice, fun) in data_slice[CURVE_FIRST_VERTEX..curves_end]
+            .chunks_exact_mut(points)
+            .zip(funs)
+        {
+            let mut x = x_start;
+            for point in curve_slice {
+                point[0] = x;
+                point[1] = fun(x);
+                x += step;
+            }
         }
     }
 
-    pub fn set_transform(&mut self, position: &Vector3<f32>, scale: f32) {
-        let data_slice = unsafe {
+    /// Sets the visible world rectangle from its center and half of its size on each axis.
+    /// The distances along the lines depend on it, so this must follow `fill_graph_buffer`
+    pub fn set_transform(&mut self, center: &Vector2<f32>, half_extent: &Vector2<f32>) {
+        let extent = self.target.extent(&self.bvk);
+        let pixels_per_unit = Vector2::new(
+            extent.width as f32 / (2.0f32 * half_extent.x),
+            extent.height as f32 / (2.0f32 * half_extent.y),
+        );
+        let points = extent.width as usize;
+        let curves = self.curves_visible.len();
+        // points of the selection rectangle, when there is no selection they are all placed
+        // outside of the visible area
+        let outside = center + 4.0f32 * half_extent;
+        let [corner_a, corner_b] = self.selection.unwrap_or([outside, outside]);
+        let x_lines = grid::lines(
+            center.x - half_extent.x,
+            center.x + half_extent.x,
+            extent.width as f32,
+        );
+        let y_lines = grid::lines(
+            center.y - half_extent.y,
+            center.y + half_extent.y,
+            extent.height as f32,
+        );
+        let labels = grid::labels(
+            center - half_extent,
+            center + half_extent,
+            Vector2::new(extent.width as f64, extent.height as f64),
+        );
+        let data_slice = self.points_mut();
+
+        // points of the y axis
+        data_slice[2][0] = 0.0f32;
+        data_slice[2][1] = center.y + half_extent.y;
+        data_slice[3][0] = 0.0f32;
+        data_slice[3][1] = center.y - half_extent.y;
+
+        let rectangle = [
+            [corner_a.x, corner_a.y, 0.0f32],
+            [corner_b.x, corner_a.y, 0.0f32],
+            [corner_b.x, corner_b.y, 0.0f32],
+            [corner_a.x, corner_b.y, 0.0f32],
+            [corner_a.x, corner_a.y, 0.0f32],
+        ];
+        data_slice[AXES_VERTICES..GRID_FIRST_VERTEX].copy_from_slice(&rectangle);
+        write_distances(
+            &mut data_slice[AXES_VERTICES..GRID_FIRST_VERTEX],
+            &pixels_per_unit,
+        );
+
+        // points of the grid lines, each line has its own pair of points and the unused ones are
+        // placed outside of the visible area
+        let grid_slice = &mut data_slice[GRID_FIRST_VERTEX..LABEL_FIRST_VERTEX];
+        grid_slice.fill([outside.x, outside.y, 0.0f32]);
+        let (x_grid_slice, y_grid_slice) = grid_slice.split_at_mut(2 * grid::MAX_LINES);
+        for (line, x) in x_grid_slice.chunks_exact_mut(2).zip(x_lines) {
+            line[0] = [x, center.y + half_extent.y, 0.0f32];
+            line[1] = [x, center.y - half_extent.y, 0.0f32];
+        }
+        for (line, y) in y_grid_slice.chunks_exact_mut(2).zip(y_lines) {
+            line[0] = [center.x - half_extent.x, y, 0.0f32];
+            line[1] = [center.x + half_extent.x, y, 0.0f32];
+        }
+
+        // points of the strokes of the labels, converted from the pixels of the target. The strokes
+        // are separated by a point that is not finite, so that no segment joins them, and the
+        // unused points are not finite too
+        let label_slice = &mut data_slice[LABEL_FIRST_VERTEX..CURVE_FIRST_VERTEX];
+        label_slice.fill([f32::NAN, f32::NAN, 0.0f32]);
+        let mut next_point = 0;
+        for strokes in labels.iter().map(PageLabel::strokes) {
+            let points = strokes.iter().map(|stroke| stroke.len() + 1).sum::<usize>();
+            if next_point + points > LABEL_VERTICES {
+                break;
+            }
+            for stroke in strokes {
+                for point in stroke {
+                    label_slice[next_point] = [
+                        center.x - half_extent.x + point.x as f32 / pixels_per_unit.x,
+                        center.y + half_extent.y - point.y as f32 / pixels_per_unit.y,
+                        0.0f32,
+                    ];
+                    next_point += 1;
+                }
+                next_point += 1;
+            }
+        }
+
+        let curves_end = CURVE_FIRST_VERTEX + curves * points;
+        for curve_slice in data_slice[CURVE_FIRST_VERTEX..curves_end].chunks_exact_mut(points) {
+            write_distances(curve_slice, &pixels_per_unit);
+        }
+
+        // the y coordinate is flipped in the vertex shader, so only the x one needs to be negated
+        let translation =
+            Matrix4::<f32>::new_translation(&Vector3::new(-center.x, center.y, 0.0f32));
+        let scaling = Matrix4::<f32>::new_nonuniform_scaling(&Vector3::new(
+            1.0f32 / half_extent.x,
+            1.0f32 / half_extent.y,
+            1.0f32,
+        ));
+        let transform = scaling * translation;
+        let uniform_data = unsafe {
             std::slice::from_raw_parts_mut(
-                self
-                    .host_curve_buffer
+                self.transform_uniform_buffer
                     .allocation
                     .mapped_ptr()
                     .unwrap()
-                    .as_ptr() as *mut [f32; 2],
-                self.host_curve_buffer.allocation.size() as usize / std::mem::size_of::<[f32; 2]>(),
+                    .as_ptr() as *mut f32,
+                18,
             )
         };
+        uniform_data[..16].copy_from_slice(transform.data.as_slice());
+        uniform_data[16..].copy_from_slice(&[extent.width as f32, extent.height as f32]);
+    }
 
-        // points of the y axis
-        data_slice[2][0] = 0.0f32;
-        data_slice[2][1] = position.y + 1.0f32 / scale;
-        data_slice[3][0] = 0.0f32;
-        data_slice[3][1] = position.y - 1.0f32 / scale;
+    /// Sets the opposite corners, in world coordinates, of the rectangle that is drawn while the
+    /// user selects a region. It is written to the vertex buffer by the next `set_transform`
+    pub fn set_selection(&mut self, selection: Option<[Vector2<f32>; 2]>) {
+        self.selection = selection;
+    }
 
-        let translation = Matrix4::<f32>::new_translation(position);
-        let scaling = Matrix4::<f32>::new_scaling(scale);
-        let transform = scaling * translation;
-        let dst_ptr = std::ptr::slice_from_raw_parts_mut(
-            self.transform_uniform_buffer
-                .allocation
-                .mapped_ptr()
-                .unwrap()
-                .as_ptr() as *mut f32,
-            self.transform_uniform_buffer.allocation.size() as usize / std::mem::size_of::<f32>(),
-        );
-        unsafe {
-            (*dst_ptr).copy_from_slice(transform.data.as_slice());
+    pub fn curve_count(&self) -> usize {
+        self.curves_visible.len()
+    }
+
+    /// Changes the number of curves, the new ones are visible and have the default style and the
+    /// color of the theme. The points of the curves must be written again with `fill_graph_buffer`
+    /// and this records the command buffers again like `set_curve_visible`
+    pub fn set_curve_count(&mut self, count: usize) -> Result<(), FPlotError> {
+        if self.curves_visible.len() != count {
+            self.curves_visible.resize(count, true);
+            self.curve_styles.resize(count, LineStyle::default());
+            self.curve_colors.resize(count, None);
+            self.wait_idle()?;
+            self.recreate_curve_vertex_buffers(self.target.extent(&self.bvk).width as usize)?;
+            self.prepare()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_curve_visible(&self, index: usize) -> bool {
+        self.curves_visible[index]
+    }
+
+    /// Shows or hides a curve, since the draws are recorded once this waits for the device to be
+    /// idle and records the command buffers again
+    pub fn set_curve_visible(&mut self, index: usize, visible: bool) -> Result<(), FPlotError> {
+        if self.curves_visible[index] != visible {
+            self.curves_visible[index] = visible;
+            self.wait_idle()?;
+            self.prepare()?;
+        }
+        Ok(())
+    }
+
+    pub fn curve_style(&self, index: usize) -> LineStyle {
+        self.curve_styles[index]
+    }
+
+    /// Changes the width and the dash pattern of a curve, this records the command buffers again
+    /// like `set_curve_visible`
+    pub fn set_curve_style(&mut self, index: usize, style: LineStyle) -> Result<(), FPlotError> {
+        if self.curve_styles[index] != style {
+            self.curve_styles[index] = style;
+            self.wait_idle()?;
+            self.prepare()?;
+        }
+        Ok(())
+    }
+
+    /// Color of a curve, the one of the theme unless it has been replaced
+    pub fn curve_color(&self, index: usize) -> Color {
+        self.curve_colors[index].unwrap_or_else(|| self.theme.curve_color(index))
+    }
+
+    /// Replaces the color that a curve takes from the theme, or restores it with `None`. This
+    /// records the command buffers again like `set_curve_visible`
+    pub fn set_curve_color(
+        &mut self,
+        index: usize,
+        color: Option<Color>,
+    ) -> Result<(), FPlotError> {
+        if self.curve_colors[index] != color {
+            self.curve_colors[index] = color;
+            self.wait_idle()?;
+            self.prepare()?;
+        }
+        Ok(())
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Changes the colors of the plot, this records the command buffers again like
+    /// `set_curve_visible`
+    pub fn set_theme(&mut self, theme: Theme) -> Result<(), FPlotError> {
+        if self.theme != theme {
+            self.theme = theme;
+            self.wait_idle()?;
+            self.prepare()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_grid_visible(&self) -> bool {
+        self.grid_visible
+    }
+
+    pub fn set_grid_visible(&mut self, visible: bool) -> Result<(), FPlotError> {
+        if self.grid_visible != visible {
+            self.grid_visible = visible;
+            self.wait_idle()?;
+            self.prepare()?;
+        }
+        Ok(())
+    }
+
+    /// Picks the highest sample count supported for color attachments that does not exceed the
+    /// requested one
+    fn select_sample_count(bvk: &BaseVk, requested: u32) -> vk::SampleCountFlags {
+        let supported = bvk
+            .get_physical_device_properties()
+            .limits
+            .framebuffer_color_sample_counts;
+        let selected = [
+            vk::SampleCountFlags::TYPE_8,
+            vk::SampleCountFlags::TYPE_4,
+            vk::SampleCountFlags::TYPE_2,
+        ]
+        .into_iter()
+        .find(|samples| samples.as_raw() <= requested && supported.contains(*samples))
+        .unwrap_or(vk::SampleCountFlags::TYPE_1);
+        if selected.as_raw() != requested {
+            eprintln!(
+                "Warning: {}x MSAA is not supported, using {}x",
+                requested,
+                selected.as_raw()
+            );
         }
+        selected
     }
 
-    fn create_renderpass(bvk: &BaseVk) -> vk::RenderPass {
-        let attachment_descriptions = vk::AttachmentDescription::builder()
-            .format(bvk.swapchain_create_info.unwrap().image_format)
+    /// When multisampling the scene is drawn to a transient image that is then resolved to the
+    /// swapchain image, this creates that image and its view
+    fn create_multisampled_target(
+        bvk: &mut BaseVk,
+        format: vk::Format,
+        extent: vk::Extent2D,
+        samples: vk::SampleCountFlags,
+    ) -> Result<Option<(ImageAllocation, vk::ImageView)>, FPlotError> {
+        if samples == vk::SampleCountFlags::TYPE_1 {
+            return Ok(None);
+        }
+        let image_create_info = vk::ImageCreateInfo::builder()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(format)
+            .extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(samples)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+            )
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        let image = bvk.allocate_image(&image_create_info, MemoryLocation::GpuOnly)?;
+        match Self::create_color_image_view(bvk, image.image, format) {
+            Ok(image_view) => Ok(Some((image, image_view))),
+            Err(e) => {
+                bvk.destroy_image(&image);
+                Err(e)
+            }
+        }
+    }
+
+    fn destroy_multisampled_target(&mut self) {
+        if let Some((image, image_view)) = self.multisampled_target.take() {
+            unsafe { self.bvk.device.destroy_image_view(image_view, None) };
+            self.bvk.destroy_image(&image);
+        }
+    }
+
+    /// The target image is always attachment 0, when multisampling the multisampled image is
+    /// attachment 1 and it is resolved to the target one at the end of the subpass
+    fn create_renderpass(
+        bvk: &BaseVk,
+        format: vk::Format,
+        final_layout: vk::ImageLayout,
+        samples: vk::SampleCountFlags,
+    ) -> Result<vk::RenderPass, FPlotError> {
+        let multisampled = samples != vk::SampleCountFlags::TYPE_1;
+        let mut attachment_descriptions = vec![vk::AttachmentDescription::builder()
+            .format(format)
             .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .load_op(if multisampled {
+                vk::AttachmentLoadOp::DONT_CARE
+            } else {
+                vk::AttachmentLoadOp::CLEAR
+            })
             .store_op(vk::AttachmentStoreOp::STORE)
             .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
+            .final_layout(final_layout)
+            .build()];
+        if multisampled {
+            attachment_descriptions.push(
+                vk::AttachmentDescription::builder()
+                    .format(format)
+                    .samples(samples)
+                    .load_op(vk::AttachmentLoadOp::CLEAR)
+                    .store_op(vk::AttachmentStoreOp::DONT_CARE)
+                    .initial_layout(vk::ImageLayout::UNDEFINED)
+                    .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                    .build(),
+            );
+        }
 
-        let attachment_references = vk::AttachmentReference::builder()
+        let target_attachment_reference = vk::AttachmentReference::builder()
             .attachment(0)
             .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+        let multisampled_attachment_reference = vk::AttachmentReference::builder()
+            .attachment(1)
+            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
         let subpass_description = vk::SubpassDescription::builder()
-            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-            .color_attachments(std::slice::from_ref(&attachment_references));
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS);
+        let subpass_description = if multisampled {
+            subpass_description
+                .color_attachments(std::slice::from_ref(&multisampled_attachment_reference))
+                .resolve_attachments(std::slice::from_ref(&target_attachment_reference))
+        } else {
+            subpass_description
+                .color_attachments(std::slice::from_ref(&target_attachment_reference))
+        };
 
         let renderpass_create_info = vk::RenderPassCreateInfo::builder()
-            .attachments(std::slice::from_ref(&attachment_descriptions))
+            .attachments(&attachment_descriptions)
             .subpasses(std::slice::from_ref(&subpass_description));
-        unsafe {
-            bvk.device
-                .create_render_pass(&renderpass_create_info, None)
-                .unwrap()
-        }
+        unsafe { bvk.device.create_render_pass(&renderpass_create_info, None) }
+            .map_err(|e| device_error("cannot create the render pass", e))
     }
 
     fn create_descriptor_set_layout(
         bvk: &BaseVk,
-    ) -> (vk::DescriptorSetLayout, vk::DescriptorPoolSize) {
+    ) -> Result<(vk::DescriptorSetLayout, vk::DescriptorPoolSize), FPlotError> {
         let descriptor_bindings: [vk::DescriptorSetLayoutBinding; 1] =
             [vk::DescriptorSetLayoutBinding::builder()
                 .binding(0)
@@ -252,15 +945,15 @@ impl GraphVk {
         let dsl = unsafe {
             bvk.device
                 .create_descriptor_set_layout(&descriptor_set_layout_create_info, None)
-                .unwrap()
-        };
-        (
+        }
+        .map_err(|e| device_error("cannot create the descriptor set layout", e))?;
+        Ok((
             dsl,
             vk::DescriptorPoolSize {
                 ty: vk::DescriptorType::UNIFORM_BUFFER,
                 descriptor_count: 1,
             },
-        )
+        ))
     }
 
     fn create_graph_pipeline(
@@ -268,20 +961,29 @@ impl GraphVk {
         shader_dir: &std::path::Path,
         renderpass: vk::RenderPass,
         descriptor_set_layout: vk::DescriptorSetLayout,
-    ) -> (vk::PipelineLayout, vk::Pipeline) {
+        samples: vk::SampleCountFlags,
+    ) -> Result<(vk::PipelineLayout, vk::Pipeline), FPlotError> {
         // Creating the shader modules
-        let vertex_shader = super::get_binary_shader_data(shader_dir.join("vertex.vert.spirv"));
-        let vertex_shader_module = unsafe {
-            bvk.device
-                .create_shader_module(&vertex_shader.2, None)
-                .unwrap()
-        };
-        let fragment_shader = super::get_binary_shader_data(shader_dir.join("fragment.frag.spirv"));
-        let fragment_shader_module = unsafe {
-            bvk.device
-                .create_shader_module(&fragment_shader.2, None)
-                .unwrap()
-        };
+        let vertex_shader_path = shader_dir.join("vertex.vert.spirv");
+        let fragment_shader_path = shader_dir.join("fragment.frag.spirv");
+        let vertex_shader = super::get_binary_shader_data(&vertex_shader_path)?;
+        let fragment_shader = super::get_binary_shader_data(&fragment_shader_path)?;
+        let shader_module_error =
+            |path: &std::path::Path, e: vk::Result| FPlotError::ShaderLoading {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            };
+        let vertex_shader_module =
+            unsafe { bvk.device.create_shader_module(&vertex_shader.2, None) }
+                .map_err(|e| shader_module_error(&vertex_shader_path, e))?;
+        let fragment_shader_module =
+            match unsafe { bvk.device.create_shader_module(&fragment_shader.2, None) } {
+                Ok(module) => module,
+                Err(e) => {
+                    unsafe { bvk.device.destroy_shader_module(vertex_shader_module, None) };
+                    return Err(shader_module_error(&fragment_shader_path, e));
+                }
+            };
         let shader_entry_point_name = unsafe { CStr::from_bytes_with_nul_unchecked(b"main\0") };
         let pipeline_shader_stage_create_infos: [vk::PipelineShaderStageCreateInfo; 2] = [
             vk::PipelineShaderStageCreateInfo::builder()
@@ -296,18 +998,25 @@ impl GraphVk {
                 .build(),
         ];
 
-        // Vertex state definition
+        // Vertex state definition, each instance is a segment and reads two consecutive points
         let vertex_input_binding = vk::VertexInputBindingDescription::builder()
             .binding(0)
-            .stride(2 * std::mem::size_of::<f32>() as u32)
-            .input_rate(vk::VertexInputRate::VERTEX);
-        let vertex_input_attribute: [vk::VertexInputAttributeDescription; 1] =
-            [vk::VertexInputAttributeDescription::builder()
+            .stride(size_of::<Point>() as u32)
+            .input_rate(vk::VertexInputRate::INSTANCE);
+        let vertex_input_attribute: [vk::VertexInputAttributeDescription; 2] = [
+            vk::VertexInputAttributeDescription::builder()
                 .location(0)
                 .binding(0)
-                .format(vk::Format::R32G32_SFLOAT)
+                .format(vk::Format::R32G32B32_SFLOAT)
                 .offset(0)
-                .build()];
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .location(1)
+                .binding(0)
+                .format(vk::Format::R32G32B32_SFLOAT)
+                .offset(size_of::<Point>() as u32)
+                .build(),
+        ];
         let pipeline_vertex_input_state_create_info =
             vk::PipelineVertexInputStateCreateInfo::builder()
                 .vertex_binding_descriptions(std::slice::from_ref(&vertex_input_binding))
@@ -315,7 +1024,7 @@ impl GraphVk {
 
         let pipeline_input_assembly_create_info =
             vk::PipelineInputAssemblyStateCreateInfo::builder()
-                .topology(vk::PrimitiveTopology::LINE_STRIP)
+                .topology(vk::PrimitiveTopology::TRIANGLE_STRIP)
                 .primitive_restart_enable(false);
 
         // Dummy values for viewport and scissor since they will be set using dynamic states
@@ -342,7 +1051,7 @@ impl GraphVk {
             vk::PipelineRasterizationStateCreateInfo::builder()
                 .depth_clamp_enable(false)
                 .rasterizer_discard_enable(false)
-                .polygon_mode(vk::PolygonMode::LINE)
+                .polygon_mode(vk::PolygonMode::FILL)
                 .cull_mode(vk::CullModeFlags::NONE)
                 .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
                 .depth_bias_enable(false)
@@ -350,7 +1059,7 @@ impl GraphVk {
 
         let pipeline_multisample_state_create_info =
             vk::PipelineMultisampleStateCreateInfo::builder()
-                .rasterization_samples(vk::SampleCountFlags::TYPE_1);
+                .rasterization_samples(samples);
 
         let color_blend_attachment_state = vk::PipelineColorBlendAttachmentState::builder()
             .blend_enable(false)
@@ -371,16 +1080,26 @@ impl GraphVk {
             vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(&dynamic_states);
 
         let push_constant_range = vk::PushConstantRange::builder()
-            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
             .offset(0)
-            .size(size_of::<Vector4<f32>>() as u32);
+            .size(size_of::<LinePushConstants>() as u32);
         let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo::builder()
             .set_layouts(std::slice::from_ref(&descriptor_set_layout))
             .push_constant_ranges(std::slice::from_ref(&push_constant_range));
-        let pipeline_layout = unsafe {
+        let destroy_shader_modules = || unsafe {
+            bvk.device.destroy_shader_module(vertex_shader_module, None);
+            bvk.device
+                .destroy_shader_module(fragment_shader_module, None);
+        };
+        let pipeline_layout = match unsafe {
             bvk.device
                 .create_pipeline_layout(&pipeline_layout_create_info, None)
-                .unwrap()
+        } {
+            Ok(pipeline_layout) => pipeline_layout,
+            Err(e) => {
+                destroy_shader_modules();
+                return Err(device_error("cannot create the pipeline layout", e));
+            }
         };
 
         let graphics_pipeline_create_info = vk::GraphicsPipelineCreateInfo::builder()
@@ -397,52 +1116,75 @@ impl GraphVk {
             .subpass(0);
 
         let pipeline = unsafe {
-            bvk.device
-                .create_graphics_pipelines(
-                    vk::PipelineCache::null(),
-                    std::slice::from_ref(&graphics_pipeline_create_info),
-                    None,
-                )
-                .unwrap()
+            bvk.device.create_graphics_pipelines(
+                vk::PipelineCache::null(),
+                std::slice::from_ref(&graphics_pipeline_create_info),
+                None,
+            )
         };
 
-        unsafe {
-            bvk.device.destroy_shader_module(vertex_shader_module, None);
-            bvk.device
-                .destroy_shader_module(fragment_shader_module, None);
+        destroy_shader_modules();
+        match pipeline {
+            Ok(pipeline) => Ok((pipeline_layout, pipeline[0])),
+            Err((_, e)) => {
+                unsafe { bvk.device.destroy_pipeline_layout(pipeline_layout, None) };
+                Err(device_error("cannot create the graphics pipeline", e))
+            }
         }
-        (pipeline_layout, pipeline[0])
     }
 
-    fn create_framebuffer(bvk: &BaseVk, renderpass: vk::RenderPass) -> vk::Framebuffer {
-        let framebuffer_attachments_image_info = vk::FramebufferAttachmentImageInfo::builder()
-            .usage(bvk.swapchain_create_info.unwrap().image_usage)
-            .width(bvk.swapchain_create_info.unwrap().image_extent.width)
-            .height(bvk.swapchain_create_info.unwrap().image_extent.width)
-            .layer_count(1)
-            .view_formats(std::slice::from_ref(&bvk.swapchain_create_info.as_ref().unwrap().image_format));
-        let mut framebuffer_attachments_create_info = vk::FramebufferAttachmentsCreateInfoKHR::builder()
-            .attachment_image_infos(std::slice::from_ref(&framebuffer_attachments_image_info));
+    fn create_framebuffer(
+        bvk: &BaseVk,
+        renderpass: vk::RenderPass,
+        usage: vk::ImageUsageFlags,
+        format: vk::Format,
+        extent: vk::Extent2D,
+        samples: vk::SampleCountFlags,
+    ) -> Result<vk::Framebuffer, FPlotError> {
+        let mut framebuffer_attachments_image_infos =
+            vec![vk::FramebufferAttachmentImageInfo::builder()
+                .usage(usage)
+                .width(extent.width)
+                .height(extent.height)
+                .layer_count(1)
+                .view_formats(std::slice::from_ref(&format))
+                .build()];
+        if samples != vk::SampleCountFlags::TYPE_1 {
+            let mut multisampled_image_info = framebuffer_attachments_image_infos[0];
+            multisampled_image_info.usage =
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT;
+            framebuffer_attachments_image_infos.push(multisampled_image_info);
+        }
+        let mut framebuffer_attachments_create_info =
+            vk::FramebufferAttachmentsCreateInfoKHR::builder()
+                .attachment_image_infos(&framebuffer_attachments_image_infos);
         let mut framebuffer_create_info = vk::FramebufferCreateInfo::builder()
             .push_next(&mut framebuffer_attachments_create_info)
             .flags(vk::FramebufferCreateFlags::IMAGELESS_KHR)
             .render_pass(renderpass)
-            .width(bvk.swapchain_create_info.unwrap().image_extent.width)
-            .height(bvk.swapchain_create_info.unwrap().image_extent.height)
+            .width(extent.width)
+            .height(extent.height)
             .layers(1);
-        framebuffer_create_info.attachment_count = 1;
+        framebuffer_create_info.attachment_count = framebuffer_attachments_image_infos.len() as u32;
         unsafe {
             bvk.device
-               .create_framebuffer(&framebuffer_create_info, None)
-               .unwrap()
+                .create_framebuffer(&framebuffer_create_info, None)
         }
+        .map_err(|e| device_error("cannot create the framebuffer", e))
     }
 
-    pub fn prepare(&mut self) {
+    /// Writes the descriptor sets and records the command buffers that draw the frames
+    pub fn prepare(&mut self) -> Result<(), FPlotError> {
         self.write_descriptor_sets();
         self.frames_data
             .iter()
-            .for_each(|e| self.record_static_command_buffers(&e.main_command));
+            .try_for_each(|e| self.record_static_command_buffers(&e.main_command))
+    }
+
+    /// Waits for the frames in flight, so that their command buffers can be recorded again
+    fn wait_idle(&self) -> Result<(), FPlotError> {
+        unsafe { self.bvk.device.device_wait_idle() }
+            .map_err(|e| device_error("cannot wait for the device", e))
     }
 
     fn write_descriptor_sets(&self) {
@@ -463,12 +1205,13 @@ impl GraphVk {
         }
     }
 
-    fn record_static_command_buffers(&self, cmri: &CommandRecordInfo) {
+    fn record_static_command_buffers(&self, cmri: &CommandRecordInfo) -> Result<(), FPlotError> {
+        let image_views = self.target.image_views(&self.bvk);
         unsafe {
             self.bvk
                 .device
                 .reset_command_pool(cmri.pool, vk::CommandPoolResetFlags::empty())
-                .unwrap();
+                .map_err(|e| device_error("cannot reset a command pool", e))?;
         }
         for (i, cmd_buf) in cmri.buffers.iter().enumerate() {
             let command_buffer_begin_info = vk::CommandBufferBeginInfo::default();
@@ -476,201 +1219,872 @@ impl GraphVk {
                 self.bvk
                     .device
                     .begin_command_buffer(*cmd_buf, &command_buffer_begin_info)
-                    .unwrap();
-                let region = vk::BufferCopy::builder()
-                    .src_offset(0)
-                    .dst_offset(0)
-                    .size(self.host_curve_buffer.allocation.size());
-                self.bvk.device.cmd_copy_buffer(
-                    *cmd_buf,
-                    self.host_curve_buffer.buffer,
-                    self.device_curve_buffer.buffer,
-                    std::slice::from_ref(&region),
-                );
-
-                let buffer_memory_barrier = vk::BufferMemoryBarrier2KHR::builder()
-                    .src_stage_mask(vk::PipelineStageFlags2KHR::COPY)
-                    .src_access_mask(vk::AccessFlags2KHR::TRANSFER_WRITE)
-                    .dst_stage_mask(vk::PipelineStageFlags2KHR::VERTEX_ATTRIBUTE_INPUT)
-                    .dst_access_mask(vk::AccessFlags2KHR::VERTEX_ATTRIBUTE_READ)
-                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-                    .buffer(self.device_curve_buffer.buffer)
-                    .offset(0)
-                    .size(vk::WHOLE_SIZE);
-                let dependancy_info = vk::DependencyInfoKHR::builder()
-                    .buffer_memory_barriers(std::slice::from_ref(&buffer_memory_barrier));
-                self.sync2.cmd_pipeline_barrier2(*cmd_buf, &dependancy_info);
-
-                let mut renderpass_attachment_begin_info = vk::RenderPassAttachmentBeginInfoKHR::builder()
-                    .attachments(std::slice::from_ref(&self.bvk.swapchain_image_views.as_ref().unwrap()[i]));
-                let mut clear_values = vk::ClearValue::default();
-                clear_values.color.float32 = [0.0f32, 0.0f32, 0.0f32, 0.0f32];
-                let renderpass_begin_info = vk::RenderPassBeginInfo::builder()
-                    .push_next(&mut renderpass_attachment_begin_info)
-                    .render_pass(self.renderpass)
-                    .framebuffer(self.framebuffer)
-                    .render_area(vk::Rect2D {
-                        offset: vk::Offset2D { x: 0, y: 0 },
-                        extent: self.bvk.swapchain_create_info.unwrap().image_extent,
-                    })
-                    .clear_values(std::slice::from_ref(&clear_values));
-                self.bvk.device.cmd_begin_render_pass(
-                    *cmd_buf,
-                    &renderpass_begin_info,
-                    vk::SubpassContents::INLINE,
-                );
-
-                self.bvk.device.cmd_bind_pipeline(
-                    *cmd_buf,
-                    vk::PipelineBindPoint::GRAPHICS,
-                    self.pipeline,
-                );
-                let viewport = vk::Viewport::builder()
-                    .x(0.0f32)
-                    .y(0.0f32)
-                    .width(self.bvk.swapchain_create_info.unwrap().image_extent.width as f32)
-                    .height(self.bvk.swapchain_create_info.unwrap().image_extent.height as f32)
-                    .min_depth(0.0f32)
-                    .max_depth(1.0f32);
+                    .map_err(|e| device_error("cannot record a command buffer", e))?;
+                self.cmd_draw_frame(*cmd_buf, self.renderpass, self.framebuffer, image_views[i]);
+                if let RenderTarget::Offscreen(target) = &self.target {
+                    self.cmd_copy_to_readback_buffer(
+                        *cmd_buf,
+                        target.image.image,
+                        vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                        target.extent,
+                        target.readback_buffer.buffer,
+                    );
+                }
                 self.bvk
                     .device
-                    .cmd_set_viewport(*cmd_buf, 0, std::slice::from_ref(&viewport));
-                let scissor = vk::Rect2D::builder()
-                    .offset(vk::Offset2D { x: 0, y: 0 })
-                    .extent(self.bvk.swapchain_create_info.unwrap().image_extent);
-                self.bvk
-                    .device
-                    .cmd_set_scissor(*cmd_buf, 0, std::slice::from_ref(&scissor));
-                self.bvk.device.cmd_bind_descriptor_sets(
-                    *cmd_buf,
-                    vk::PipelineBindPoint::GRAPHICS,
-                    self.pipeline_layout,
-                    0,
-                    std::slice::from_ref(&self.descriptor_info.buffers[0]),
-                    &[],
-                );
-                self.bvk.device.cmd_bind_vertex_buffers(
-                    *cmd_buf,
-                    0,
-                    std::slice::from_ref(&self.device_curve_buffer.buffer),
-                    std::slice::from_ref(&0),
-                );
-                // Drawing of the axes
-                let axes_color = Vector4::<f32>::new(1.0, 0.0, 0.0, 0.0);
-                self.bvk.device.cmd_push_constants(
-                    *cmd_buf,
-                    self.pipeline_layout,
-                    vk::ShaderStageFlags::FRAGMENT,
-                    0,
-                    std::slice::from_raw_parts(axes_color.as_ptr() as *const u8, 16),
-                );
-                self.bvk.device.cmd_draw(*cmd_buf, 2, 1, 0, 0);
-                self.bvk.device.cmd_draw(*cmd_buf, 2, 1, 2, 0);
-                // Drawing of the function
-                let function_color = Vector4::<f32>::new(1.0, 1.0, 1.0, 0.0);
-                self.bvk.device.cmd_push_constants(
-                    *cmd_buf,
-                    self.pipeline_layout,
-                    vk::ShaderStageFlags::FRAGMENT,
-                    0,
-                    std::slice::from_raw_parts(function_color.as_ptr() as *const u8, 16),
+                    .end_command_buffer(*cmd_buf)
+                    .map_err(|e| device_error("cannot record a command buffer", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the upload of the vertices and the render pass that draws the plot to the given
+    /// image view, with a framebuffer compatible with the render pass
+    unsafe fn cmd_draw_frame(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        renderpass: vk::RenderPass,
+        framebuffer: vk::Framebuffer,
+        image_view: vk::ImageView,
+    ) {
+        let extent = self.target.extent(&self.bvk);
+        let region = vk::BufferCopy::builder()
+            .src_offset(0)
+            .dst_offset(0)
+            .size(self.host_curve_buffer.allocation.size());
+        self.bvk.device.cmd_copy_buffer(
+            cmd_buf,
+            self.host_curve_buffer.buffer,
+            self.device_curve_buffer.buffer,
+            std::slice::from_ref(&region),
+        );
+
+        let buffer_memory_barrier = vk::BufferMemoryBarrier2KHR::builder()
+            .src_stage_mask(vk::PipelineStageFlags2KHR::COPY)
+            .src_access_mask(vk::AccessFlags2KHR::TRANSFER_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2KHR::VERTEX_ATTRIBUTE_INPUT)
+            .dst_access_mask(vk::AccessFlags2KHR::VERTEX_ATTRIBUTE_READ)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .buffer(self.device_curve_buffer.buffer)
+            .offset(0)
+            .size(vk::WHOLE_SIZE)
+            .build();
+        self.cmd_pipeline_barrier(cmd_buf, std::slice::from_ref(&buffer_memory_barrier), &[]);
+
+        let mut attachments = vec![image_view];
+        if let Some((_, multisampled_image_view)) = self.multisampled_target.as_ref() {
+            attachments.push(*multisampled_image_view);
+        }
+        let mut renderpass_attachment_begin_info =
+            vk::RenderPassAttachmentBeginInfoKHR::builder().attachments(&attachments);
+        let mut clear_value = vk::ClearValue::default();
+        clear_value.color.float32 = self.theme.background;
+        let clear_values = vec![clear_value; attachments.len()];
+        let renderpass_begin_info = vk::RenderPassBeginInfo::builder()
+            .push_next(&mut renderpass_attachment_begin_info)
+            .render_pass(renderpass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent,
+            })
+            .clear_values(&clear_values);
+        self.bvk.device.cmd_begin_render_pass(
+            cmd_buf,
+            &renderpass_begin_info,
+            vk::SubpassContents::INLINE,
+        );
+
+        self.bvk.device.cmd_bind_pipeline(
+            cmd_buf,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline,
+        );
+        let viewport = vk::Viewport::builder()
+            .x(0.0f32)
+            .y(0.0f32)
+            .width(extent.width as f32)
+            .height(extent.height as f32)
+            .min_depth(0.0f32)
+            .max_depth(1.0f32);
+        self.bvk
+            .device
+            .cmd_set_viewport(cmd_buf, 0, std::slice::from_ref(&viewport));
+        let scissor = vk::Rect2D::builder()
+            .offset(vk::Offset2D { x: 0, y: 0 })
+            .extent(extent);
+        self.bvk
+            .device
+            .cmd_set_scissor(cmd_buf, 0, std::slice::from_ref(&scissor));
+        self.bvk.device.cmd_bind_descriptor_sets(
+            cmd_buf,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline_layout,
+            0,
+            std::slice::from_ref(&self.descriptor_info.buffers[0]),
+            &[],
+        );
+        self.bvk.device.cmd_bind_vertex_buffers(
+            cmd_buf,
+            0,
+            std::slice::from_ref(&self.device_curve_buffer.buffer),
+            std::slice::from_ref(&0),
+        );
+        // Drawing of the grid, one line at a time
+        if self.grid_visible {
+            for first_point in (GRID_FIRST_VERTEX..LABEL_FIRST_VERTEX).step_by(2) {
+                self.cmd_draw_line(
+                    cmd_buf,
+                    self.theme.grid,
+                    &LineStyle::GRID,
+                    first_point,
+                    2,
                 );
-                self.bvk.device.cmd_draw(
-                    *cmd_buf,
-                    self.bvk.swapchain_create_info.unwrap().image_extent.width,
-                    1,
-                    4,
-                    0,
+            }
+        }
+        // Drawing of the axes and of the labels of the grid lines, which are all a single line
+        self.cmd_draw_line(cmd_buf, self.theme.axis, &LineStyle::AXIS, 0, 2);
+        self.cmd_draw_line(cmd_buf, self.theme.axis, &LineStyle::AXIS, 2, 2);
+        let label_style = LineStyle {
+            width: font::stroke_width(LABEL_FONT_SIZE) as f32,
+            pattern: DashPattern::Solid,
+        };
+        self.cmd_draw_line(
+            cmd_buf,
+            self.theme.axis,
+            &label_style,
+            LABEL_FIRST_VERTEX,
+            LABEL_VERTICES,
+        );
+        // Drawing of the functions, in order so that the last one is above the others
+        let points = extent.width as usize;
+        for (i, style) in self.curve_styles.iter().enumerate() {
+            if self.curves_visible[i] {
+                self.cmd_draw_line(
+                    cmd_buf,
+                    self.curve_color(i),
+                    style,
+                    CURVE_FIRST_VERTEX + i * points,
+                    points,
                 );
-                self.bvk.device.cmd_end_render_pass(*cmd_buf);
-                self.bvk.device.end_command_buffer(*cmd_buf).unwrap();
             }
         }
+        // Drawing of the selection rectangle, above everything else
+        self.cmd_draw_line(
+            cmd_buf,
+            self.theme.selection,
+            &LineStyle::SELECTION,
+            AXES_VERTICES,
+            SELECTION_VERTICES,
+        );
+        self.bvk.device.cmd_end_render_pass(cmd_buf);
     }
 
-    pub fn present_loop(&mut self, window: &winit::window::Window) {
-        let current_frame_data =
-            &self.frames_data[self.frames_count as usize % self.frames_data.len()];
+    /// Records the draw of the line strip made of the given range of points of the vertex buffer,
+    /// one instance for each of its segments
+    unsafe fn cmd_draw_line(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        color: Color,
+        style: &LineStyle,
+        first_point: usize,
+        points: usize,
+    ) {
+        let (dash_length, gap_length) = style.pattern.lengths(style.width);
+        let push_constants = LinePushConstants {
+            color,
+            width: style.width,
+            dash_length,
+            gap_length,
+        };
+        self.bvk.device.cmd_push_constants(
+            cmd_buf,
+            self.pipeline_layout,
+            vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+            0,
+            std::slice::from_raw_parts(
+                &push_constants as *const LinePushConstants as *const u8,
+                size_of::<LinePushConstants>(),
+            ),
+        );
+        self.bvk.device.cmd_draw(
+            cmd_buf,
+            SEGMENT_VERTICES,
+            points.saturating_sub(1) as u32,
+            0,
+            first_point as u32,
+        );
+    }
+
+    /// Records the copy of a rendered image in the given layout to a readback buffer, which is
+    /// then made visible to the host. The image is left in `final_layout`
+    unsafe fn cmd_copy_to_readback_buffer(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        final_layout: vk::ImageLayout,
+        extent: vk::Extent2D,
+        readback_buffer: vk::Buffer,
+    ) {
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .base_mip_level(0)
+            .level_count(1)
+            .base_array_layer(0)
+            .layer_count(1)
+            .build();
+        let image_memory_barrier = vk::ImageMemoryBarrier2KHR::builder()
+            .src_stage_mask(vk::PipelineStageFlags2KHR::COLOR_ATTACHMENT_OUTPUT)
+            .src_access_mask(vk::AccessFlags2KHR::COLOR_ATTACHMENT_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2KHR::COPY)
+            .dst_access_mask(vk::AccessFlags2KHR::TRANSFER_READ)
+            .old_layout(layout)
+            .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+        self.cmd_pipeline_barrier(cmd_buf, &[], std::slice::from_ref(&image_memory_barrier));
+
+        let region = vk::BufferImageCopy::builder()
+            .buffer_offset(0)
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(
+                vk::ImageSubresourceLayers::builder()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .mip_level(0)
+                    .base_array_layer(0)
+                    .layer_count(1)
+                    .build(),
+            )
+            .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+            .image_extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            });
+        self.bvk.device.cmd_copy_image_to_buffer(
+            cmd_buf,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            readback_buffer,
+            std::slice::from_ref(&region),
+        );
+
+        let buffer_memory_barrier = vk::BufferMemoryBarrier2KHR::builder()
+            .src_stage_mask(vk::PipelineStageFlags2KHR::COPY)
+            .src_access_mask(vk::AccessFlags2KHR::TRANSFER_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2KHR::HOST)
+            .dst_access_mask(vk::AccessFlags2KHR::HOST_READ)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .buffer(readback_buffer)
+            .offset(0)
+            .size(vk::WHOLE_SIZE)
+            .build();
+        let image_memory_barrier = vk::ImageMemoryBarrier2KHR::builder()
+            .src_stage_mask(vk::PipelineStageFlags2KHR::COPY)
+            .src_access_mask(vk::AccessFlags2KHR::TRANSFER_READ)
+            .dst_stage_mask(vk::PipelineStageFlags2KHR::BOTTOM_OF_PIPE)
+            .dst_access_mask(vk::AccessFlags2KHR::empty())
+            .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .new_layout(final_layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+        let image_memory_barriers: &[vk::ImageMemoryBarrier2KHR] = match final_layout {
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL => &[][..],
+            _ => std::slice::from_ref(&image_memory_barrier),
+        };
+        self.cmd_pipeline_barrier(
+            cmd_buf,
+            std::slice::from_ref(&buffer_memory_barrier),
+            image_memory_barriers,
+        );
+    }
+
+    /// Records a pipeline barrier with synchronization2 when the device supports it, otherwise
+    /// with the equivalent barriers of Vulkan 1.0
+    unsafe fn cmd_pipeline_barrier(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        buffer_memory_barriers: &[vk::BufferMemoryBarrier2KHR],
+        image_memory_barriers: &[vk::ImageMemoryBarrier2KHR],
+    ) {
+        if let Some(sync2) = self.sync2.as_ref() {
+            let dependancy_info = vk::DependencyInfoKHR::builder()
+                .buffer_memory_barriers(buffer_memory_barriers)
+                .image_memory_barriers(image_memory_barriers);
+            sync2.cmd_pipeline_barrier2(cmd_buf, &dependancy_info);
+            return;
+        }
+
+        // Vulkan 1.0 barriers share the stages of the whole command
+        let mut src_stage_mask = vk::PipelineStageFlags::empty();
+        let mut dst_stage_mask = vk::PipelineStageFlags::empty();
+        let mut buffer_barriers = Vec::with_capacity(buffer_memory_barriers.len());
+        for barrier in buffer_memory_barriers {
+            src_stage_mask |= legacy_stage_mask(barrier.src_stage_mask);
+            dst_stage_mask |= legacy_stage_mask(barrier.dst_stage_mask);
+            buffer_barriers.push(
+                vk::BufferMemoryBarrier::builder()
+                    .src_access_mask(legacy_access_mask(barrier.src_access_mask))
+                    .dst_access_mask(legacy_access_mask(barrier.dst_access_mask))
+                    .src_queue_family_index(barrier.src_queue_family_index)
+                    .dst_queue_family_index(barrier.dst_queue_family_index)
+                    .buffer(barrier.buffer)
+                    .offset(barrier.offset)
+                    .size(barrier.size)
+                    .build(),
+            );
+        }
+        let mut image_barriers = Vec::with_capacity(image_memory_barriers.len());
+        for barrier in image_memory_barriers {
+            src_stage_mask |= legacy_stage_mask(barrier.src_stage_mask);
+            dst_stage_mask |= legacy_stage_mask(barrier.dst_stage_mask);
+            image_barriers.push(
+                vk::ImageMemoryBarrier::builder()
+                    .src_access_mask(legacy_access_mask(barrier.src_access_mask))
+                    .dst_access_mask(legacy_access_mask(barrier.dst_access_mask))
+                    .old_layout(barrier.old_layout)
+                    .new_layout(barrier.new_layout)
+                    .src_queue_family_index(barrier.src_queue_family_index)
+                    .dst_queue_family_index(barrier.dst_queue_family_index)
+                    .image(barrier.image)
+                    .subresource_range(barrier.subresource_range)
+                    .build(),
+            );
+        }
+        self.bvk.device.cmd_pipeline_barrier(
+            cmd_buf,
+            src_stage_mask,
+            dst_stage_mask,
+            vk::DependencyFlags::empty(),
+            &[],
+            &buffer_barriers,
+            &image_barriers,
+        );
+    }
+
+    /**
+    Submits the command buffers to the first queue with synchronization2 when the device supports
+    it, otherwise with Vulkan 1.0. The execution waits for `wait_semaphore` at the given stages and
+    `signal_semaphore` is signaled when all the commands are done.
+    */
+    unsafe fn queue_submit(
+        &self,
+        command_buffers: &[vk::CommandBuffer],
+        wait_semaphore: Option<(vk::Semaphore, vk::PipelineStageFlags2KHR)>,
+        signal_semaphore: Option<vk::Semaphore>,
+        fence: vk::Fence,
+    ) -> Result<(), vk::Result> {
+        if let Some(sync2) = self.sync2.as_ref() {
+            let wait_semaphore_submit_infos = wait_semaphore
+                .iter()
+                .map(|(semaphore, stage_mask)| {
+                    vk::SemaphoreSubmitInfoKHR::builder()
+                        .semaphore(*semaphore)
+                        .stage_mask(*stage_mask)
+                        .device_index(0)
+                        .build()
+                })
+                .collect::<Vec<_>>();
+            let command_submit_infos = command_buffers
+                .iter()
+                .map(|command_buffer| {
+                    vk::CommandBufferSubmitInfoKHR::builder()
+                        .command_buffer(*command_buffer)
+                        .device_mask(0)
+                        .build()
+                })
+                .collect::<Vec<_>>();
+            let signal_semaphore_submit_infos = signal_semaphore
+                .iter()
+                .map(|semaphore| {
+                    vk::SemaphoreSubmitInfoKHR::builder()
+                        .semaphore(*semaphore)
+                        .stage_mask(vk::PipelineStageFlags2KHR::ALL_COMMANDS)
+                        .device_index(0)
+                        .build()
+                })
+                .collect::<Vec<_>>();
+            let submit_info = vk::SubmitInfo2KHR::builder()
+                .wait_semaphore_infos(&wait_semaphore_submit_infos)
+                .command_buffer_infos(&command_submit_infos)
+                .signal_semaphore_infos(&signal_semaphore_submit_infos)
+                .build();
+            return sync2.queue_submit2(
+                self.bvk.queues[0],
+                std::slice::from_ref(&submit_info),
+                fence,
+            );
+        }
+
+        let (wait_semaphores, wait_stage_masks): (Vec<_>, Vec<_>) = wait_semaphore
+            .iter()
+            .map(|(semaphore, stage_mask)| (*semaphore, legacy_stage_mask(*stage_mask)))
+            .unzip();
+        let signal_semaphores = signal_semaphore.iter().copied().collect::<Vec<_>>();
+        let submit_info = vk::SubmitInfo::builder()
+            .wait_semaphores(&wait_semaphores)
+            .wait_dst_stage_mask(&wait_stage_masks)
+            .command_buffers(command_buffers)
+            .signal_semaphores(&signal_semaphores)
+            .build();
+        self.bvk
+            .device
+            .queue_submit(self.bvk.queues[0], std::slice::from_ref(&submit_info), fence)
+    }
+
+    /// Draws a frame of a headless renderer and returns its pixels as rows of RGBA bytes, from the
+    /// top one to the bottom one
+    pub fn render_offscreen(&mut self) -> Result<Vec<u8>, FPlotError> {
+        let target = match &self.target {
+            RenderTarget::Offscreen(target) => target,
+            RenderTarget::Swapchain => {
+                return Err(FPlotError::Target(
+                    "the renderer draws to a window, not off-screen".to_string(),
+                ))
+            }
+        };
+        self.submit_and_wait(self.frames_data[0].main_command.buffers[0])?;
+        Ok(read_readback_buffer(&target.readback_buffer, target.extent))
+    }
+
+    /// Submits the command buffer with the fence of the first frame and waits for its execution,
+    /// the fence must not be in use
+    fn submit_and_wait(&self, cmd_buf: vk::CommandBuffer) -> Result<(), FPlotError> {
+        let fence = self.frames_data[0].after_exec_fence;
         unsafe {
-            let res = self.bvk.swapchain_fn.as_ref().unwrap().acquire_next_image(
+            self.bvk
+                .device
+                .reset_fences(std::slice::from_ref(&fence))
+                .map_err(|e| device_error("cannot reset a fence", e))?;
+            self.queue_submit(std::slice::from_ref(&cmd_buf), None, None, fence)
+                .map_err(|e| device_error("cannot submit to the queue", e))?;
+            self.bvk
+                .device
+                .wait_for_fences(std::slice::from_ref(&fence), false, u64::MAX)
+                .map_err(|e| device_error("cannot wait for a fence", e))
+        }
+    }
+
+    /**
+    Captures the plot shown in the window and returns its width, its height and its pixels as rows
+    of RGBA bytes. When the swapchain images can be a transfer source a new frame is presented and
+    copied from its swapchain image, otherwise the frame is drawn again to an off-screen image with
+    the same format. Either way the pixels are the ones of the window, already in sRGB.
+    */
+    pub fn screenshot(&mut self) -> Result<(u32, u32, Vec<u8>), FPlotError> {
+        if let RenderTarget::Offscreen(_) = self.target {
+            return Err(FPlotError::Target(
+                "the renderer draws off-screen, not to a window".to_string(),
+            ));
+        }
+        let format = self.target.format(&self.bvk);
+        let extent = self.target.extent(&self.bvk);
+        let bgra = match format {
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => true,
+            vk::Format::R8G8B8A8_UNORM
+            | vk::Format::R8G8B8A8_SRGB
+            | vk::Format::A8B8G8R8_UNORM_PACK32
+            | vk::Format::A8B8G8R8_SRGB_PACK32 => false,
+            _ => {
+                return Err(FPlotError::Export(format!(
+                    "the swapchain format {:?} is not supported",
+                    format
+                )))
+            }
+        };
+
+        // the frames in flight would otherwise use the command buffers and the fences
+        self.wait_idle()?;
+        let copy_command = self.bvk.create_cmd_pool_and_buffers(
+            vk::CommandPoolCreateFlags::TRANSIENT,
+            vk::CommandBufferLevel::PRIMARY,
+            1,
+        )?;
+        let pixels = if self
+            .target
+            .usage(&self.bvk)
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            self.capture_swapchain_image(copy_command.buffers[0], extent)
+        } else {
+            self.capture_offscreen_image(copy_command.buffers[0], format, extent)
+        };
+        self.bvk.destroy_cmd_pool_and_buffers(&copy_command);
+
+        let mut pixels = pixels?;
+        for pixel in pixels.chunks_exact_mut(4) {
+            if bgra {
+                pixel.swap(0, 2);
+            }
+            // the window is opaque, whatever alpha has been written
+            pixel[3] = u8::MAX;
+        }
+        Ok((extent.width, extent.height, pixels))
+    }
+
+    /// Presents a new frame and copies its swapchain image to a readback buffer with the given
+    /// command buffer, the swapchain images must have the transfer source usage
+    fn capture_swapchain_image(
+        &mut self,
+        cmd_buf: vk::CommandBuffer,
+        extent: vk::Extent2D,
+    ) -> Result<Vec<u8>, FPlotError> {
+        let readback_buffer = Self::create_readback_buffer(&mut self.bvk, extent)?;
+        let presented = self.present_and_copy(cmd_buf, extent, &readback_buffer);
+        let pixels = presented.map(|()| read_readback_buffer(&readback_buffer, extent));
+        self.bvk.destroy_buffer(&readback_buffer);
+        pixels
+    }
+
+    fn present_and_copy(
+        &mut self,
+        cmd_buf: vk::CommandBuffer,
+        extent: vk::Extent2D,
+        readback_buffer: &BufferAllocation,
+    ) -> Result<(), FPlotError> {
+        let swapchain_fn = self.bvk.swapchain_fn.as_ref().unwrap();
+        let (image_index, _) = unsafe {
+            swapchain_fn.acquire_next_image(
                 self.bvk.swapchain,
                 u64::MAX,
                 self.semaphores[0],
                 vk::Fence::null(),
+            )
+        }
+        .map_err(|e| device_error("cannot acquire a swapchain image", e))?;
+        let image = unsafe { swapchain_fn.get_swapchain_images(self.bvk.swapchain) }
+            .map_err(|e| device_error("cannot get the swapchain images", e))?[image_index as usize];
+        let frame_data = &self.frames_data[self.frames_count as usize % self.frames_data.len()];
+        unsafe {
+            let command_buffer_begin_info = vk::CommandBufferBeginInfo::builder()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+            self.bvk
+                .device
+                .begin_command_buffer(cmd_buf, &command_buffer_begin_info)
+                .map_err(|e| device_error("cannot record a command buffer", e))?;
+            self.cmd_copy_to_readback_buffer(
+                cmd_buf,
+                image,
+                vk::ImageLayout::PRESENT_SRC_KHR,
+                vk::ImageLayout::PRESENT_SRC_KHR,
+                extent,
+                readback_buffer.buffer,
             );
-            // if the swapchain is suboptimal
-            if res.is_err() || res.unwrap().1 {
-                self.bvk.device.device_wait_idle();
-                self.bvk.recreate_swapchain(
-                    self.bvk.swapchain_create_info.unwrap().present_mode,
-                    vk::Extent2D {
-                        width: window.inner_size().width,
-                        height: window.inner_size().height,
-                    },
-                    self.bvk.swapchain_create_info.unwrap().image_usage,
-                    vk::SurfaceFormatKHR {
-                        format: self.bvk.swapchain_create_info.unwrap().image_format,
-                        color_space: self.bvk.swapchain_create_info.unwrap().image_color_space,
-                    },
-                );
-                self.bvk.device.destroy_framebuffer(self.framebuffer, None);
-                self.framebuffer = Self::create_framebuffer(&self.bvk, self.renderpass);
-                self.recreate_curve_vertex_buffers(window.inner_size().width as usize);
-                self.prepare();
-                return;
+            self.bvk
+                .device
+                .end_command_buffer(cmd_buf)
+                .map_err(|e| device_error("cannot record a command buffer", e))?;
+
+            self.bvk
+                .device
+                .reset_fences(std::slice::from_ref(&frame_data.after_exec_fence))
+                .map_err(|e| device_error("cannot reset a fence", e))?;
+            // the frame is drawn by its static command buffer and then copied
+            let command_buffers = [
+                frame_data.main_command.buffers[image_index as usize],
+                cmd_buf,
+            ];
+            self.queue_submit(
+                &command_buffers,
+                Some((
+                    self.semaphores[0],
+                    vk::PipelineStageFlags2KHR::COLOR_ATTACHMENT_OUTPUT,
+                )),
+                Some(self.semaphores[1]),
+                frame_data.after_exec_fence,
+            )
+            .map_err(|e| device_error("cannot submit to the queue", e))?;
+
+            let present_info = vk::PresentInfoKHR::builder()
+                .wait_semaphores(std::slice::from_ref(&self.semaphores[1]))
+                .swapchains(std::slice::from_ref(&self.bvk.swapchain))
+                .image_indices(std::slice::from_ref(&image_index));
+            // an out of date swapchain is recreated by the next frame, the copy is done anyway
+            match swapchain_fn.queue_present(self.bvk.queues[0], &present_info) {
+                Ok(_) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => (),
+                Err(e) => return Err(device_error("cannot present the frame", e)),
+            }
+            self.bvk
+                .device
+                .wait_for_fences(
+                    std::slice::from_ref(&frame_data.after_exec_fence),
+                    false,
+                    u64::MAX,
+                )
+                .map_err(|e| device_error("cannot wait for a fence", e))?;
+        }
+        self.frames_count += 1;
+        Ok(())
+    }
+
+    /// Draws the frame to an off-screen image with the given format and reads it back with the
+    /// given command buffer, the render pass differs from the one of the window only in the final
+    /// layout of the image so the pipeline can be used with it
+    fn capture_offscreen_image(
+        &mut self,
+        cmd_buf: vk::CommandBuffer,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> Result<Vec<u8>, FPlotError> {
+        let target = Self::create_offscreen_target(&mut self.bvk, format, extent)?;
+        let renderpass = match Self::create_renderpass(
+            &self.bvk,
+            format,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            self.samples,
+        ) {
+            Ok(renderpass) => renderpass,
+            Err(e) => {
+                Self::destroy_offscreen_target(&mut self.bvk, &target);
+                return Err(e);
+            }
+        };
+        let framebuffer = match Self::create_framebuffer(
+            &self.bvk,
+            renderpass,
+            OFFSCREEN_USAGE,
+            format,
+            extent,
+            self.samples,
+        ) {
+            Ok(framebuffer) => framebuffer,
+            Err(e) => {
+                unsafe { self.bvk.device.destroy_render_pass(renderpass, None) };
+                Self::destroy_offscreen_target(&mut self.bvk, &target);
+                return Err(e);
             }
-            let res = res.unwrap();
-            self.bvk.device.wait_for_fences(
-                std::slice::from_ref(&current_frame_data.after_exec_fence),
-                false,
+        };
+        let drawn = self.draw_and_copy(cmd_buf, renderpass, framebuffer, &target);
+
+        let pixels = drawn.map(|()| read_readback_buffer(&target.readback_buffer, extent));
+        unsafe {
+            self.bvk.device.destroy_framebuffer(framebuffer, None);
+            self.bvk.device.destroy_render_pass(renderpass, None);
+        }
+        Self::destroy_offscreen_target(&mut self.bvk, &target);
+        pixels
+    }
+
+    /// Draws the frame to the off-screen target with the given command buffer and copies it to
+    /// its readback buffer
+    fn draw_and_copy(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        renderpass: vk::RenderPass,
+        framebuffer: vk::Framebuffer,
+        target: &OffscreenTarget,
+    ) -> Result<(), FPlotError> {
+        unsafe {
+            let command_buffer_begin_info = vk::CommandBufferBeginInfo::builder()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+            self.bvk
+                .device
+                .begin_command_buffer(cmd_buf, &command_buffer_begin_info)
+                .map_err(|e| device_error("cannot record a command buffer", e))?;
+            self.cmd_draw_frame(cmd_buf, renderpass, framebuffer, target.image_view);
+            self.cmd_copy_to_readback_buffer(
+                cmd_buf,
+                target.image.image,
+                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                target.extent,
+                target.readback_buffer.buffer,
+            );
+            self.bvk
+                .device
+                .end_command_buffer(cmd_buf)
+                .map_err(|e| device_error("cannot record a command buffer", e))?;
+        }
+        self.submit_and_wait(cmd_buf)
+    }
+
+    /// Draws and presents a frame, the swapchain is recreated when it no longer matches the
+    /// window. Failing to recreate it or to draw the frame is returned as an error
+    pub fn present_loop(&mut self, window: &winit::window::Window) -> Result<(), FPlotError> {
+        let current_frame_data =
+            &self.frames_data[self.frames_count as usize % self.frames_data.len()];
+        unsafe {
+            let res = self.bvk.swapchain_fn.as_ref().unwrap().acquire_next_image(
+                self.bvk.swapchain,
                 u64::MAX,
+                self.semaphores[0],
+                vk::Fence::null(),
             );
+            let image_index = match res {
+                Ok((image_index, false)) => image_index,
+                // a successful acquire signaled the semaphore, which would never be waited
+                Ok((_, true)) => return self.recreate_swapchain(window, true),
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                    return self.recreate_swapchain(window, false)
+                }
+                Err(e) => return Err(device_error("cannot acquire a swapchain image", e)),
+            };
             self.bvk
                 .device
-                .reset_fences(std::slice::from_ref(&current_frame_data.after_exec_fence));
-
-            let wait_semaphore_submit_info = vk::SemaphoreSubmitInfoKHR::builder()
-                .semaphore(self.semaphores[0])
-                .stage_mask(vk::PipelineStageFlags2KHR::COLOR_ATTACHMENT_OUTPUT)
-                .device_index(0);
-            let command_submit_info = vk::CommandBufferSubmitInfoKHR::builder()
-                .command_buffer(current_frame_data.main_command.buffers[res.0 as usize])
-                .device_mask(0);
-            let signal_semaphore_submit_info = vk::SemaphoreSubmitInfoKHR::builder()
-                .semaphore(self.semaphores[1])
-                .stage_mask(vk::PipelineStageFlags2KHR::ALL_COMMANDS)
-                .device_index(0);
-            let submit_info = vk::SubmitInfo2KHR::builder()
-                .wait_semaphore_infos(std::slice::from_ref(&wait_semaphore_submit_info))
-                .command_buffer_infos(std::slice::from_ref(&command_submit_info))
-                .signal_semaphore_infos(std::slice::from_ref(&signal_semaphore_submit_info))
-                .build();
-            self.sync2.queue_submit2(
-                self.bvk.queues[0],
-                std::slice::from_ref(&submit_info),
+                .wait_for_fences(
+                    std::slice::from_ref(&current_frame_data.after_exec_fence),
+                    false,
+                    u64::MAX,
+                )
+                .map_err(|e| device_error("cannot wait for a fence", e))?;
+            self.bvk
+                .device
+                .reset_fences(std::slice::from_ref(&current_frame_data.after_exec_fence))
+                .map_err(|e| device_error("cannot reset a fence", e))?;
+
+            self.queue_submit(
+                std::slice::from_ref(
+                    &current_frame_data.main_command.buffers[image_index as usize],
+                ),
+                Some((
+                    self.semaphores[0],
+                    vk::PipelineStageFlags2KHR::COLOR_ATTACHMENT_OUTPUT,
+                )),
+                Some(self.semaphores[1]),
                 current_frame_data.after_exec_fence,
-            ).expect("Error submitting queue");
+            )
+            .map_err(|e| device_error("cannot submit to the queue", e))?;
 
             let present_info = vk::PresentInfoKHR::builder()
                 .wait_semaphores(std::slice::from_ref(&self.semaphores[1]))
                 .swapchains(std::slice::from_ref(&self.bvk.swapchain))
-                .image_indices(std::slice::from_ref(&res.0));
-            self.bvk
+                .image_indices(std::slice::from_ref(&image_index));
+            let presented = self
+                .bvk
                 .swapchain_fn
                 .as_ref()
                 .unwrap()
                 .queue_present(self.bvk.queues[0], &present_info);
-
             self.frames_count += 1;
+            match presented {
+                Ok(false) => Ok(()),
+                // the next frame acquires an image from the recreated swapchain
+                Ok(true) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                    window.request_redraw();
+                    Ok(())
+                }
+                Err(e) => Err(device_error("cannot present the frame", e)),
+            }
+        }
+    }
+
+    /// Recreates the swapchain and the objects that depend on its size after an image could not be
+    /// acquired from it, or after it has been acquired from a suboptimal one when `acquired`
+    fn recreate_swapchain(
+        &mut self,
+        window: &winit::window::Window,
+        acquired: bool,
+    ) -> Result<(), FPlotError> {
+        self.wait_idle()?;
+        let swapchain_create_info = self.bvk.swapchain_create_info.unwrap();
+        self.bvk.recreate_swapchain(
+            swapchain_create_info.present_mode,
+            vk::Extent2D {
+                width: window.inner_size().width,
+                height: window.inner_size().height,
+            },
+            swapchain_create_info.image_usage,
+            vk::SurfaceFormatKHR {
+                format: swapchain_create_info.image_format,
+                color_space: swapchain_create_info.image_color_space,
+            },
+        )?;
+        if acquired {
+            self.bvk.destroy_semaphores(&self.semaphores);
+            self.semaphores.clear();
+            self.semaphores = self.bvk.create_semaphores(2)?;
+        }
+        let extent = self.target.extent(&self.bvk);
+        let format = self.target.format(&self.bvk);
+        self.destroy_multisampled_target();
+        self.multisampled_target =
+            Self::create_multisampled_target(&mut self.bvk, format, extent, self.samples)?;
+        unsafe { self.bvk.device.destroy_framebuffer(self.framebuffer, None) };
+        self.framebuffer = vk::Framebuffer::null();
+        self.framebuffer = Self::create_framebuffer(
+            &self.bvk,
+            self.renderpass,
+            self.target.usage(&self.bvk),
+            format,
+            extent,
+            self.samples,
+        )?;
+        self.recreate_curve_vertex_buffers(window.inner_size().width as usize)?;
+        self.prepare()?;
+        // the frame has not been presented, so it is drawn again with the new size
+        window.request_redraw();
+        Ok(())
+    }
+}
+
+/// The Vulkan 1.0 stages that include the given synchronization2 ones, the copy stage is part of
+/// the transfer one
+fn legacy_stage_mask(stage_mask: vk::PipelineStageFlags2KHR) -> vk::PipelineStageFlags {
+    let mut legacy = vk::PipelineStageFlags::from_raw(stage_mask.as_raw() as u32);
+    if stage_mask.contains(vk::PipelineStageFlags2KHR::COPY) {
+        legacy |= vk::PipelineStageFlags::TRANSFER;
+    }
+    legacy
+}
+
+/// The Vulkan 1.0 access flags of the given synchronization2 ones, the ones used here have the
+/// same bits in both
+fn legacy_access_mask(access_mask: vk::AccessFlags2KHR) -> vk::AccessFlags {
+    vk::AccessFlags::from_raw(access_mask.as_raw() as u32)
+}
+
+/// Copies the pixels of an image of the given extent out of its readback buffer
+fn read_readback_buffer(readback_buffer: &BufferAllocation, extent: vk::Extent2D) -> Vec<u8> {
+    let size = extent.width as usize * extent.height as usize * 4;
+    unsafe {
+        std::slice::from_raw_parts(
+            readback_buffer.allocation.mapped_ptr().unwrap().as_ptr() as *const u8,
+            size,
+        )
+        .to_vec()
+    }
+}
+
+/// Writes in the third coordinate of each point its distance in pixels from the first one along
+/// the line, segments with non finite points do not contribute to it
+fn write_distances(points: &mut [Point], pixels_per_unit: &Vector2<f32>) {
+    let mut distance = 0.0f32;
+    let mut previous: Option<Point> = None;
+    for point in points.iter_mut() {
+        if let Some(previous) = previous {
+            let segment = Vector2::new(
+                (point[0] - previous[0]) * pixels_per_unit.x,
+                (point[1] - previous[1]) * pixels_per_unit.y,
+            )
+            .norm();
+            if segment.is_finite() {
+                distance += segment;
+            }
         }
+        point[2] = distance;
+        previous = Some(*point);
     }
 }
 
 impl Drop for GraphVk {
     fn drop(&mut self) {
+        // the objects are destroyed anyway, there is nothing else to do when the device is lost
+        if let Err(e) = self.wait_idle() {
+            eprintln!("Warning: {}", e);
+        }
         unsafe {
-            self.bvk.device.device_wait_idle().unwrap();
             self.bvk
                 .device
                 .destroy_descriptor_set_layout(self.descriptor_set_layout, None);
@@ -691,6 +2105,10 @@ impl Drop for GraphVk {
         self.bvk.destroy_buffer(&self.host_curve_buffer);
         self.bvk.destroy_buffer(&self.device_curve_buffer);
         self.bvk.destroy_buffer(&self.transform_uniform_buffer);
+        self.destroy_multisampled_target();
+        if let RenderTarget::Offscreen(target) = &self.target {
+            Self::destroy_offscreen_target(&mut self.bvk, target);
+        }
 
         unsafe { self.bvk.device.destroy_framebuffer(self.framebuffer, None) };
         unsafe {
@@ -2,6 +2,7 @@ mod base_vk;
 pub mod graph_vk;
 mod pointer_chain_helpers;
 
+use crate::error::FPlotError;
 use ash::vk;
 use std::ffi::CStr;
 use std::fmt::Write;
@@ -33,30 +34,34 @@ unsafe extern "system" fn vk_debug_callback(
     vk::FALSE
 }
 
+/// Reads a compiled shader, whose stage is given by the extension before `.spirv`
 fn get_binary_shader_data<T: AsRef<Path>>(
     path: T,
-) -> (Vec<u8>, vk::ShaderStageFlags, vk::ShaderModuleCreateInfo) {
+) -> Result<(Vec<u8>, vk::ShaderStageFlags, vk::ShaderModuleCreateInfo), FPlotError> {
+    let path = path.as_ref();
+    let shader_error = |reason: &str| FPlotError::ShaderLoading {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    };
     let shader_type_extension = path
-        .as_ref()
         .file_stem()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .rsplit_once('.')
-        .expect("No shader type extension found")
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.rsplit_once('.'))
+        .ok_or_else(|| shader_error("no shader type extension found"))?
         .1;
     let shader_type = match shader_type_extension {
         "vert" => vk::ShaderStageFlags::VERTEX,
         "frag" => vk::ShaderStageFlags::FRAGMENT,
         "comp" => vk::ShaderStageFlags::COMPUTE,
-        _ => panic!("Shader type could not be deducted"),
+        _ => return Err(shader_error("the shader type could not be deduced")),
     };
-    let mut file = File::open(path).expect("Could not open shader");
     let mut data = Vec::<u8>::new();
-    file.read_to_end(&mut data).expect("Could not read shader");
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut data))
+        .map_err(|e| shader_error(&e.to_string()))?;
 
     let mut module_create_info = vk::ShaderModuleCreateInfo::default();
     module_create_info.code_size = data.len();
     module_create_info.p_code = data.as_ptr() as *const u32;
-    (data, shader_type, module_create_info)
+    Ok((data, shader_type, module_create_info))
 }
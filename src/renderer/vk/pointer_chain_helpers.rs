@@ -4,160 +4,847 @@ use std::ffi::c_void;
 use std::mem::size_of;
 use std::ptr::null_mut;
 
-pub unsafe fn clone_vk_physical_device_features2_structure(
-    source: &vk::PhysicalDeviceFeatures2,
-) -> vk::PhysicalDeviceFeatures2 {
-    let mut ret_val = vk::PhysicalDeviceFeatures2::default();
-
-    let mut source_ptr = source.p_next;
-    let mut dst_ptr = &mut (ret_val.p_next);
-
-    macro_rules! allocate_struct {
-        ($struct_identifier:expr, $struct_type:ty) => {{
-            let cloned_child_struct_ptr = std::alloc::alloc(Layout::new::<$struct_type>());
-            (*(cloned_child_struct_ptr as *mut $struct_type)).s_type = $struct_identifier;
-            cloned_child_struct_ptr
-        }};
-    }
-    while !source_ptr.is_null() {
-        let cloned_child_struct_ptr = match (*(source_ptr as *const vk::PhysicalDeviceFeatures2)).s_type {
-            vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_1_FEATURES =>
-                allocate_struct!(vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_1_FEATURES,
-                    vk::PhysicalDeviceVulkan11Features),
-            vk::StructureType::PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_FEATURES_EXT =>
-                allocate_struct!(vk::StructureType::PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_FEATURES_EXT,
-                    vk::PhysicalDeviceDescriptorIndexingFeatures),
-            vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES_KHR =>
-                allocate_struct!(vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES_KHR,
-                    vk::PhysicalDeviceSynchronization2FeaturesKHR),
-            vk::StructureType::PHYSICAL_DEVICE_IMAGELESS_FRAMEBUFFER_FEATURES =>
-                allocate_struct!(vk::StructureType::PHYSICAL_DEVICE_IMAGELESS_FRAMEBUFFER_FEATURES,
-                    vk::PhysicalDeviceImagelessFramebufferFeatures),
-            _ => {panic!("Found unrecognized struct inside clone_vkPhysicalDeviceFeatures2")},
-        };
-        (*(cloned_child_struct_ptr as *mut vk::PhysicalDeviceVulkan11Features)).p_next = null_mut();
-        *dst_ptr = cloned_child_struct_ptr as *mut c_void;
-        dst_ptr = &mut ((*((*dst_ptr) as *mut vk::PhysicalDeviceFeatures2)).p_next);
-        source_ptr = (*(source_ptr as *const vk::PhysicalDeviceFeatures2)).p_next;
-    }
-    ret_val
+/// A features struct that can be part of a VkPhysicalDeviceFeatures2 pointer chain
+pub struct DeviceFeaturesStruct {
+    pub s_type: vk::StructureType,
+    /// Name of the struct in the Vulkan specification
+    pub name: &'static str,
+    /// Device extension that provides the struct, None for the ones of the core versions
+    pub extension: Option<&'static str>,
+    layout: Layout,
+    /// Names of the VkBool32 members that follow s_type and p_next, in their order
+    pub features: &'static [&'static str],
 }
 
-pub unsafe fn destroy_vk_physical_device_features2(source: &mut vk::PhysicalDeviceFeatures2) {
-    let mut p_next = source.p_next;
+// The VkBool32 members follow s_type and p_next, which are laid out like a VkBaseOutStructure,
+// the ones of VkPhysicalDeviceFeatures2 are the members of its VkPhysicalDeviceFeatures
+const FEATURES_OFFSET: usize = size_of::<vk::BaseOutStructure>();
 
-    macro_rules! free_struct_and_advance {
-        ($struct_type:ty) => {{
-            let p_next_tmp = p_next;
-            p_next = (*(p_next as *const $struct_type)).p_next;
-            std::alloc::dealloc(
-                p_next_tmp as *mut u8,
-                Layout::new::<$struct_type>(),
-            );
-        }};
-
-        (match $s_type:expr; {
-            $( $feature:pat => $struct_type:ty ),*
-            $(,)?
-        }) => {
-            match $s_type {
-                $( $feature => free_struct_and_advance!($struct_type), )*
-                _ => panic!("Found unrecognized struct inside destroy_vk_physical_device_features2"),
+macro_rules! device_features_structs {
+    ($(
+        $s_type:ident => $struct_type:ty, $name:literal, $extension:expr,
+        [$($feature:literal),* $(,)?]
+    );* $(;)?) => {
+        /**
+        The features structs that a pointer chain can contain, the first one is the
+        VkPhysicalDeviceFeatures2 at the root of the chain.
+        */
+        pub static DEVICE_FEATURES_STRUCTS: &[DeviceFeaturesStruct] = &[$(
+            DeviceFeaturesStruct {
+                s_type: vk::StructureType::$s_type,
+                name: $name,
+                extension: $extension,
+                layout: Layout::new::<$struct_type>(),
+                features: &[$($feature),*],
+            }
+        ),*];
+
+        /// The default value of the struct with the given type formatted by Debug, which lists the
+        /// names of its members
+        #[cfg(test)]
+        fn debug_default_struct(s_type: vk::StructureType) -> String {
+            match s_type {
+                $(vk::StructureType::$s_type => format!("{:?}", <$struct_type>::default()),)*
+                _ => unreachable!(),
             }
         }
-    }
-    while !p_next.is_null() {
-        free_struct_and_advance!(match (*(p_next as *const vk::PhysicalDeviceFeatures2)).s_type; {
-            PHYSICAL_DEVICE_VULKAN_1_1_FEATURES => vk::PhysicalDeviceVulkan11Features,
-            PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_FEATURES_EXT => vk::PhysicalDeviceDescriptorIndexingFeaturesEXT,
-            PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES_KHR => vk::PhysicalDeviceSynchronization2FeaturesKHR,
-            PHYSICAL_DEVICE_IMAGELESS_FRAMEBUFFER_FEATURES => vk::PhysicalDeviceImagelessFramebufferFeatures,
-        });
-    }
-    source.p_next = null_mut();
+    };
 }
 
-pub unsafe fn compare_device_features_structs(
-    baseline: *const c_void,
-    desired: *const c_void,
-    mut size: usize,
-) -> bool {
-    // casting the structure to a PhysicalDeviceFeatures2 struct to compare the struct identifier
-    if (*(baseline as *const vk::PhysicalDeviceFeatures2)).s_type
-        != (*(desired as *const vk::PhysicalDeviceFeatures2)).s_type
-    {
-        return false;
-    }
-    // then we know that the structs type are the same so we cast them to a view of u32
-    // the offset has a 4 added to it because of struct padding
-    let offset = size_of::<ash::vk::StructureType>() + 4 + size_of::<*mut c_void>();
-
-    // struct at the end will have 4 more bytes due to the fact its size has to be divisible by the
-    // largest member which in this case is size_of<*mut c_void> = 8
-    size -= 4;
-
-    let baseline_data = baseline.add(offset) as *const u32;
-    let desired_data = desired.add(offset) as *const u32;
-    for i in 0..((size - offset) / size_of::<vk::Bool32>()) {
-        if *(desired_data.add(i)) > *(baseline_data.add(i)) {
-            return false;
-        }
-    }
-    true
+device_features_structs! {
+    PHYSICAL_DEVICE_FEATURES_2 => vk::PhysicalDeviceFeatures2,
+        "VkPhysicalDeviceFeatures", None, [
+        "robustBufferAccess",
+        "fullDrawIndexUint32",
+        "imageCubeArray",
+        "independentBlend",
+        "geometryShader",
+        "tessellationShader",
+        "sampleRateShading",
+        "dualSrcBlend",
+        "logicOp",
+        "multiDrawIndirect",
+        "drawIndirectFirstInstance",
+        "depthClamp",
+        "depthBiasClamp",
+        "fillModeNonSolid",
+        "depthBounds",
+        "wideLines",
+        "largePoints",
+        "alphaToOne",
+        "multiViewport",
+        "samplerAnisotropy",
+        "textureCompressionETC2",
+        "textureCompressionASTC_LDR",
+        "textureCompressionBC",
+        "occlusionQueryPrecise",
+        "pipelineStatisticsQuery",
+        "vertexPipelineStoresAndAtomics",
+        "fragmentStoresAndAtomics",
+        "shaderTessellationAndGeometryPointSize",
+        "shaderImageGatherExtended",
+        "shaderStorageImageExtendedFormats",
+        "shaderStorageImageMultisample",
+        "shaderStorageImageReadWithoutFormat",
+        "shaderStorageImageWriteWithoutFormat",
+        "shaderUniformBufferArrayDynamicIndexing",
+        "shaderSampledImageArrayDynamicIndexing",
+        "shaderStorageBufferArrayDynamicIndexing",
+        "shaderStorageImageArrayDynamicIndexing",
+        "shaderClipDistance",
+        "shaderCullDistance",
+        "shaderFloat64",
+        "shaderInt64",
+        "shaderInt16",
+        "shaderResourceResidency",
+        "shaderResourceMinLod",
+        "sparseBinding",
+        "sparseResidencyBuffer",
+        "sparseResidencyImage2D",
+        "sparseResidencyImage3D",
+        "sparseResidency2Samples",
+        "sparseResidency4Samples",
+        "sparseResidency8Samples",
+        "sparseResidency16Samples",
+        "sparseResidencyAliased",
+        "variableMultisampleRate",
+        "inheritedQueries",
+    ];
+    PHYSICAL_DEVICE_VULKAN_1_1_FEATURES => vk::PhysicalDeviceVulkan11Features,
+        "VkPhysicalDeviceVulkan11Features", None, [
+        "storageBuffer16BitAccess",
+        "uniformAndStorageBuffer16BitAccess",
+        "storagePushConstant16",
+        "storageInputOutput16",
+        "multiview",
+        "multiviewGeometryShader",
+        "multiviewTessellationShader",
+        "variablePointersStorageBuffer",
+        "variablePointers",
+        "protectedMemory",
+        "samplerYcbcrConversion",
+        "shaderDrawParameters",
+    ];
+    PHYSICAL_DEVICE_VULKAN_1_2_FEATURES => vk::PhysicalDeviceVulkan12Features,
+        "VkPhysicalDeviceVulkan12Features", None, [
+        "samplerMirrorClampToEdge",
+        "drawIndirectCount",
+        "storageBuffer8BitAccess",
+        "uniformAndStorageBuffer8BitAccess",
+        "storagePushConstant8",
+        "shaderBufferInt64Atomics",
+        "shaderSharedInt64Atomics",
+        "shaderFloat16",
+        "shaderInt8",
+        "descriptorIndexing",
+        "shaderInputAttachmentArrayDynamicIndexing",
+        "shaderUniformTexelBufferArrayDynamicIndexing",
+        "shaderStorageTexelBufferArrayDynamicIndexing",
+        "shaderUniformBufferArrayNonUniformIndexing",
+        "shaderSampledImageArrayNonUniformIndexing",
+        "shaderStorageBufferArrayNonUniformIndexing",
+        "shaderStorageImageArrayNonUniformIndexing",
+        "shaderInputAttachmentArrayNonUniformIndexing",
+        "shaderUniformTexelBufferArrayNonUniformIndexing",
+        "shaderStorageTexelBufferArrayNonUniformIndexing",
+        "descriptorBindingUniformBufferUpdateAfterBind",
+        "descriptorBindingSampledImageUpdateAfterBind",
+        "descriptorBindingStorageImageUpdateAfterBind",
+        "descriptorBindingStorageBufferUpdateAfterBind",
+        "descriptorBindingUniformTexelBufferUpdateAfterBind",
+        "descriptorBindingStorageTexelBufferUpdateAfterBind",
+        "descriptorBindingUpdateUnusedWhilePending",
+        "descriptorBindingPartiallyBound",
+        "descriptorBindingVariableDescriptorCount",
+        "runtimeDescriptorArray",
+        "samplerFilterMinmax",
+        "scalarBlockLayout",
+        "imagelessFramebuffer",
+        "uniformBufferStandardLayout",
+        "shaderSubgroupExtendedTypes",
+        "separateDepthStencilLayouts",
+        "hostQueryReset",
+        "timelineSemaphore",
+        "bufferDeviceAddress",
+        "bufferDeviceAddressCaptureReplay",
+        "bufferDeviceAddressMultiDevice",
+        "vulkanMemoryModel",
+        "vulkanMemoryModelDeviceScope",
+        "vulkanMemoryModelAvailabilityVisibilityChains",
+        "shaderOutputViewportIndex",
+        "shaderOutputLayer",
+        "subgroupBroadcastDynamicId",
+    ];
+    PHYSICAL_DEVICE_16BIT_STORAGE_FEATURES => vk::PhysicalDevice16BitStorageFeatures,
+        "VkPhysicalDevice16BitStorageFeatures", Some("VK_KHR_16bit_storage"), [
+        "storageBuffer16BitAccess",
+        "uniformAndStorageBuffer16BitAccess",
+        "storagePushConstant16",
+        "storageInputOutput16",
+    ];
+    PHYSICAL_DEVICE_MULTIVIEW_FEATURES => vk::PhysicalDeviceMultiviewFeatures,
+        "VkPhysicalDeviceMultiviewFeatures", Some("VK_KHR_multiview"), [
+        "multiview",
+        "multiviewGeometryShader",
+        "multiviewTessellationShader",
+    ];
+    PHYSICAL_DEVICE_8BIT_STORAGE_FEATURES => vk::PhysicalDevice8BitStorageFeatures,
+        "VkPhysicalDevice8BitStorageFeatures", Some("VK_KHR_8bit_storage"), [
+        "storageBuffer8BitAccess",
+        "uniformAndStorageBuffer8BitAccess",
+        "storagePushConstant8",
+    ];
+    PHYSICAL_DEVICE_SHADER_ATOMIC_INT64_FEATURES => vk::PhysicalDeviceShaderAtomicInt64Features,
+        "VkPhysicalDeviceShaderAtomicInt64Features", Some("VK_KHR_shader_atomic_int64"), [
+        "shaderBufferInt64Atomics",
+        "shaderSharedInt64Atomics",
+    ];
+    PHYSICAL_DEVICE_SHADER_FLOAT16_INT8_FEATURES => vk::PhysicalDeviceShaderFloat16Int8Features,
+        "VkPhysicalDeviceShaderFloat16Int8Features", Some("VK_KHR_shader_float16_int8"), [
+        "shaderFloat16",
+        "shaderInt8",
+    ];
+    PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_FEATURES => vk::PhysicalDeviceDescriptorIndexingFeatures,
+        "VkPhysicalDeviceDescriptorIndexingFeatures", Some("VK_EXT_descriptor_indexing"), [
+        "shaderInputAttachmentArrayDynamicIndexing",
+        "shaderUniformTexelBufferArrayDynamicIndexing",
+        "shaderStorageTexelBufferArrayDynamicIndexing",
+        "shaderUniformBufferArrayNonUniformIndexing",
+        "shaderSampledImageArrayNonUniformIndexing",
+        "shaderStorageBufferArrayNonUniformIndexing",
+        "shaderStorageImageArrayNonUniformIndexing",
+        "shaderInputAttachmentArrayNonUniformIndexing",
+        "shaderUniformTexelBufferArrayNonUniformIndexing",
+        "shaderStorageTexelBufferArrayNonUniformIndexing",
+        "descriptorBindingUniformBufferUpdateAfterBind",
+        "descriptorBindingSampledImageUpdateAfterBind",
+        "descriptorBindingStorageImageUpdateAfterBind",
+        "descriptorBindingStorageBufferUpdateAfterBind",
+        "descriptorBindingUniformTexelBufferUpdateAfterBind",
+        "descriptorBindingStorageTexelBufferUpdateAfterBind",
+        "descriptorBindingUpdateUnusedWhilePending",
+        "descriptorBindingPartiallyBound",
+        "descriptorBindingVariableDescriptorCount",
+        "runtimeDescriptorArray",
+    ];
+    PHYSICAL_DEVICE_SCALAR_BLOCK_LAYOUT_FEATURES => vk::PhysicalDeviceScalarBlockLayoutFeatures,
+        "VkPhysicalDeviceScalarBlockLayoutFeatures", Some("VK_EXT_scalar_block_layout"), [
+        "scalarBlockLayout",
+    ];
+    PHYSICAL_DEVICE_VULKAN_MEMORY_MODEL_FEATURES => vk::PhysicalDeviceVulkanMemoryModelFeatures,
+        "VkPhysicalDeviceVulkanMemoryModelFeatures", Some("VK_KHR_vulkan_memory_model"), [
+        "vulkanMemoryModel",
+        "vulkanMemoryModelDeviceScope",
+        "vulkanMemoryModelAvailabilityVisibilityChains",
+    ];
+    PHYSICAL_DEVICE_IMAGELESS_FRAMEBUFFER_FEATURES =>
+        vk::PhysicalDeviceImagelessFramebufferFeatures,
+        "VkPhysicalDeviceImagelessFramebufferFeatures", Some("VK_KHR_imageless_framebuffer"), [
+        "imagelessFramebuffer",
+    ];
+    PHYSICAL_DEVICE_UNIFORM_BUFFER_STANDARD_LAYOUT_FEATURES =>
+        vk::PhysicalDeviceUniformBufferStandardLayoutFeatures,
+        "VkPhysicalDeviceUniformBufferStandardLayoutFeatures",
+        Some("VK_KHR_uniform_buffer_standard_layout"), [
+        "uniformBufferStandardLayout",
+    ];
+    PHYSICAL_DEVICE_SEPARATE_DEPTH_STENCIL_LAYOUTS_FEATURES =>
+        vk::PhysicalDeviceSeparateDepthStencilLayoutsFeatures,
+        "VkPhysicalDeviceSeparateDepthStencilLayoutsFeatures",
+        Some("VK_KHR_separate_depth_stencil_layouts"), [
+        "separateDepthStencilLayouts",
+    ];
+    PHYSICAL_DEVICE_HOST_QUERY_RESET_FEATURES => vk::PhysicalDeviceHostQueryResetFeatures,
+        "VkPhysicalDeviceHostQueryResetFeatures", Some("VK_EXT_host_query_reset"), [
+        "hostQueryReset",
+    ];
+    PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES => vk::PhysicalDeviceTimelineSemaphoreFeatures,
+        "VkPhysicalDeviceTimelineSemaphoreFeatures", Some("VK_KHR_timeline_semaphore"), [
+        "timelineSemaphore",
+    ];
+    PHYSICAL_DEVICE_BUFFER_DEVICE_ADDRESS_FEATURES => vk::PhysicalDeviceBufferDeviceAddressFeatures,
+        "VkPhysicalDeviceBufferDeviceAddressFeatures", Some("VK_KHR_buffer_device_address"), [
+        "bufferDeviceAddress",
+        "bufferDeviceAddressCaptureReplay",
+        "bufferDeviceAddressMultiDevice",
+    ];
+    PHYSICAL_DEVICE_LINE_RASTERIZATION_FEATURES_EXT =>
+        vk::PhysicalDeviceLineRasterizationFeaturesEXT,
+        "VkPhysicalDeviceLineRasterizationFeaturesEXT", Some("VK_EXT_line_rasterization"), [
+        "rectangularLines",
+        "bresenhamLines",
+        "smoothLines",
+        "stippledRectangularLines",
+        "stippledBresenhamLines",
+        "stippledSmoothLines",
+    ];
+    PHYSICAL_DEVICE_EXTENDED_DYNAMIC_STATE_FEATURES_EXT =>
+        vk::PhysicalDeviceExtendedDynamicStateFeaturesEXT,
+        "VkPhysicalDeviceExtendedDynamicStateFeaturesEXT", Some("VK_EXT_extended_dynamic_state"), [
+        "extendedDynamicState",
+    ];
+    PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES_KHR =>
+        vk::PhysicalDeviceSynchronization2FeaturesKHR,
+        "VkPhysicalDeviceSynchronization2Features", Some("VK_KHR_synchronization2"), [
+        "synchronization2",
+    ];
+    PHYSICAL_DEVICE_DYNAMIC_RENDERING_FEATURES_KHR => vk::PhysicalDeviceDynamicRenderingFeaturesKHR,
+        "VkPhysicalDeviceDynamicRenderingFeatures", Some("VK_KHR_dynamic_rendering"), [
+        "dynamicRendering",
+    ];
+    PHYSICAL_DEVICE_MAINTENANCE_4_FEATURES_KHR => vk::PhysicalDeviceMaintenance4FeaturesKHR,
+        "VkPhysicalDeviceMaintenance4Features", Some("VK_KHR_maintenance4"), [
+        "maintenance4",
+    ];
 }
 
-pub unsafe fn compare_vk_physical_device_features2(
-    baseline: &vk::PhysicalDeviceFeatures2,
-    desired: &vk::PhysicalDeviceFeatures2,
-) -> bool {
-    if !compare_device_features_structs(
-        baseline as *const vk::PhysicalDeviceFeatures2 as *const c_void,
-        desired as *const vk::PhysicalDeviceFeatures2 as *const c_void,
-        size_of::<vk::PhysicalDeviceFeatures2>(),
-    ) {
-        return false;
-    }
-
-    let mut baseline_ptr = baseline.p_next;
-    let mut desired_ptr = desired.p_next;
-    while !baseline_ptr.is_null() && !desired_ptr.is_null() {
-        let res: bool;
-        match (*(baseline_ptr as *const vk::PhysicalDeviceFeatures2)).s_type {
-            vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_1_FEATURES => {
-                res = compare_device_features_structs(
-                    baseline_ptr,
-                    desired_ptr,
-                    size_of::<vk::PhysicalDeviceVulkan11Features>(),
-                );
+/// The registered features struct with the given structure type
+pub fn device_features_struct(s_type: vk::StructureType) -> Option<&'static DeviceFeaturesStruct> {
+    DEVICE_FEATURES_STRUCTS.iter().find(|s| s.s_type == s_type)
+}
+
+/// The registered features structs, besides the VkPhysicalDeviceFeatures2, that a device with the
+/// given version of Vulkan and extensions provides
+pub fn available_device_features_structs<'a>(
+    api_version: u32,
+    extensions: &'a [&'a str],
+) -> impl Iterator<Item = &'static DeviceFeaturesStruct> + 'a {
+    DEVICE_FEATURES_STRUCTS[1..]
+        .iter()
+        .filter(move |s| match s.extension {
+            Some(extension) => extensions.contains(&extension),
+            // the structs of the core versions, Vulkan11Features and Vulkan12Features, are part
+            // of Vulkan 1.2
+            None => api_version >= vk::API_VERSION_1_2,
+        })
+}
+
+/**
+A VkPhysicalDeviceFeatures2 pointer chain that owns the structs after the first one, which are
+allocated and freed by it. Only the registered structs can be part of it and each one at most once,
+the structs with the same type of two chains are compared and combined regardless of their order.
+*/
+pub struct DeviceFeaturesChain {
+    features2: vk::PhysicalDeviceFeatures2,
+    // The structs of the pointer chain after features2, in order
+    structs: Vec<(&'static DeviceFeaturesStruct, *mut c_void)>,
+}
+
+impl DeviceFeaturesChain {
+    /// A chain with the structs of the given types after the VkPhysicalDeviceFeatures2 and all the
+    /// features disabled, the types must be registered and not repeated
+    pub fn new(s_types: &[vk::StructureType]) -> Result<Self, String> {
+        let mut chain = Self::default();
+        for s_type in s_types {
+            chain.push(*s_type)?;
+        }
+        Ok(chain)
+    }
+
+    /// A chain with the same structs and all the features disabled
+    pub fn without_features(&self) -> Self {
+        let mut chain = Self::default();
+        for (info, _) in &self.structs {
+            chain.push_struct(info);
+        }
+        chain
+    }
+
+    /// The VkPhysicalDeviceFeatures2 at the root of the chain
+    pub fn features2(&self) -> &vk::PhysicalDeviceFeatures2 {
+        &self.features2
+    }
+
+    /**
+    The VkPhysicalDeviceFeatures2 at the root of the chain, to be filled by Vulkan.
+
+    # Safety
+    The p_next pointers of the chain must not be changed.
+    */
+    pub unsafe fn features2_mut(&mut self) -> &mut vk::PhysicalDeviceFeatures2 {
+        &mut self.features2
+    }
+
+    /// Whether the feature of the struct of the given type is enabled, None when the chain does
+    /// not have such struct or such feature
+    pub fn get(&self, s_type: vk::StructureType, feature: &str) -> Option<bool> {
+        let (info, values) = self.struct_features(s_type)?;
+        let i = info.features.iter().position(|name| *name == feature)?;
+        Some(values[i] != vk::FALSE)
+    }
+
+    /// Enables or disables the feature of the struct of the given type, which is added to the
+    /// chain when missing
+    pub fn set(
+        &mut self,
+        s_type: vk::StructureType,
+        feature: &str,
+        enabled: bool,
+    ) -> Result<(), String> {
+        if s_type != vk::StructureType::PHYSICAL_DEVICE_FEATURES_2
+            && !self.structs.iter().any(|(info, _)| info.s_type == s_type)
+        {
+            self.push(s_type)?;
+        }
+        let (info, values) = self.struct_features_mut(s_type).unwrap();
+        let i = info
+            .features
+            .iter()
+            .position(|name| *name == feature)
+            .ok_or_else(|| format!("{} has no feature named {}", info.name, feature))?;
+        values[i] = enabled as vk::Bool32;
+        Ok(())
+    }
+
+    /// The name of each struct of the chain along with the names and the values of its features
+    pub fn features(&self) -> Vec<(&'static str, Vec<(&'static str, bool)>)> {
+        self.all_structs()
+            .map(|(info, struct_ptr)| {
+                let values = unsafe { features_slice(struct_ptr, info) };
+                let features = info
+                    .features
+                    .iter()
+                    .zip(values)
+                    .map(|(name, value)| (*name, *value != vk::FALSE))
+                    .collect();
+                (info.name, features)
+            })
+            .collect()
+    }
+
+    /// Whether every feature enabled in `desired` is enabled in this chain too, the structs that
+    /// this chain does not have count as having all the features disabled
+    pub fn supports(&self, desired: &DeviceFeaturesChain) -> bool {
+        desired.all_structs().all(|(info, struct_ptr)| {
+            let desired_values = unsafe { features_slice(struct_ptr, info) };
+            let values = self.struct_features(info.s_type).map(|(_, values)| values);
+            desired_values.iter().enumerate().all(|(i, desired)| {
+                *desired == vk::FALSE || matches!(values, Some(values) if values[i] != vk::FALSE)
+            })
+        })
+    }
+
+    /// Enables the features enabled in `source`, the structs of `source` with enabled features
+    /// that this chain does not have are added to it
+    pub fn merge(&mut self, source: &DeviceFeaturesChain) {
+        for (info, struct_ptr) in source.all_structs() {
+            let source_values = unsafe { features_slice(struct_ptr, info) };
+            if source_values.iter().all(|value| *value == vk::FALSE) {
+                continue;
             }
-            vk::StructureType::PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_FEATURES_EXT => {
-                res = compare_device_features_structs(
-                    baseline_ptr,
-                    desired_ptr,
-                    size_of::<vk::PhysicalDeviceDescriptorIndexingFeaturesEXT>(),
-                );
+            if self.struct_features(info.s_type).is_none() {
+                self.push_struct(info);
             }
-            vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES_KHR => {
-                res = compare_device_features_structs(
-                    baseline_ptr,
-                    desired_ptr,
-                    size_of::<vk::PhysicalDeviceSynchronization2FeaturesKHR>(),
-                );
+            let (_, values) = self.struct_features_mut(info.s_type).unwrap();
+            for (value, source) in values.iter_mut().zip(source_values) {
+                if *source != vk::FALSE {
+                    *value = vk::TRUE;
+                }
             }
-            vk::StructureType::PHYSICAL_DEVICE_IMAGELESS_FRAMEBUFFER_FEATURES => {
-                res = compare_device_features_structs(
-                    baseline_ptr,
-                    desired_ptr,
-                    size_of::<vk::PhysicalDeviceImagelessFramebufferFeatures>(),
-                );
+        }
+    }
+
+    /// Disables the features that are not enabled in `available`, all the ones of the structs
+    /// that `available` does not have
+    pub fn intersect(&mut self, available: &DeviceFeaturesChain) {
+        let structs = self.all_structs_mut().collect::<Vec<_>>();
+        for (info, struct_ptr) in structs {
+            let values = unsafe { features_slice_mut(struct_ptr, info) };
+            let available_values = available.struct_features(info.s_type).map(|(_, v)| v);
+            for (i, value) in values.iter_mut().enumerate() {
+                if !matches!(available_values, Some(available) if available[i] != vk::FALSE) {
+                    *value = vk::FALSE;
+                }
             }
-            _ => panic!("Found unrecognized struct inside compare_vk_physical_device_features2"),
         }
-        if !res {
-            return false;
+    }
+
+    /// Appends a struct of the given type with all the features disabled and returns it
+    fn push(&mut self, s_type: vk::StructureType) -> Result<*mut c_void, String> {
+        let info = device_features_struct(s_type)
+            .ok_or_else(|| format!("unsupported features struct {:?}", s_type))?;
+        if s_type == vk::StructureType::PHYSICAL_DEVICE_FEATURES_2 {
+            return Err(format!("{} can only be the first struct", info.name));
+        }
+        if self.structs.iter().any(|(other, _)| other.s_type == s_type) {
+            return Err(format!("{} is already part of the chain", info.name));
+        }
+        Ok(self.push_struct(info))
+    }
+
+    fn push_struct(&mut self, info: &'static DeviceFeaturesStruct) -> *mut c_void {
+        unsafe {
+            let struct_ptr = std::alloc::alloc_zeroed(info.layout) as *mut c_void;
+            if struct_ptr.is_null() {
+                std::alloc::handle_alloc_error(info.layout);
+            }
+            (*(struct_ptr as *mut vk::BaseOutStructure)).s_type = info.s_type;
+            match self.structs.last() {
+                Some((_, last)) => {
+                    (*(*last as *mut vk::BaseOutStructure)).p_next = struct_ptr as *mut _
+                }
+                None => self.features2.p_next = struct_ptr,
+            }
+            self.structs.push((info, struct_ptr));
+            struct_ptr
         }
-        baseline_ptr = (*(baseline_ptr as *const vk::PhysicalDeviceFeatures2)).p_next;
-        desired_ptr = (*(desired_ptr as *const vk::PhysicalDeviceFeatures2)).p_next;
     }
-    baseline_ptr.is_null() && desired_ptr.is_null()
+
+    /// Every struct of the chain, starting from the VkPhysicalDeviceFeatures2
+    fn all_structs(
+        &self,
+    ) -> impl Iterator<Item = (&'static DeviceFeaturesStruct, *const c_void)> + '_ {
+        let features2 = std::ptr::addr_of!(self.features2) as *const c_void;
+        std::iter::once((&DEVICE_FEATURES_STRUCTS[0], features2)).chain(
+            self.structs
+                .iter()
+                .map(|(info, struct_ptr)| (*info, *struct_ptr as *const c_void)),
+        )
+    }
+
+    /// Every struct of the chain like `all_structs`, with pointers that can be written through
+    fn all_structs_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (&'static DeviceFeaturesStruct, *mut c_void)> + '_ {
+        let features2 = std::ptr::addr_of_mut!(self.features2) as *mut c_void;
+        std::iter::once((&DEVICE_FEATURES_STRUCTS[0], features2))
+            .chain(self.structs.iter().copied())
+    }
+
+    fn struct_features(
+        &self,
+        s_type: vk::StructureType,
+    ) -> Option<(&'static DeviceFeaturesStruct, &[vk::Bool32])> {
+        self.all_structs()
+            .find(|(info, _)| info.s_type == s_type)
+            .map(|(info, struct_ptr)| (info, unsafe { features_slice(struct_ptr, info) }))
+    }
+
+    fn struct_features_mut(
+        &mut self,
+        s_type: vk::StructureType,
+    ) -> Option<(&'static DeviceFeaturesStruct, &mut [vk::Bool32])> {
+        self.all_structs_mut()
+            .find(|(info, _)| info.s_type == s_type)
+            .map(|(info, struct_ptr)| (info, unsafe { features_slice_mut(struct_ptr, info) }))
+    }
+}
+
+impl Default for DeviceFeaturesChain {
+    /// A chain with only the VkPhysicalDeviceFeatures2, with all the features disabled
+    fn default() -> Self {
+        DeviceFeaturesChain {
+            features2: vk::PhysicalDeviceFeatures2::default(),
+            structs: Vec::new(),
+        }
+    }
+}
+
+impl Clone for DeviceFeaturesChain {
+    fn clone(&self) -> Self {
+        let mut chain = self.without_features();
+        chain.features2.features = self.features2.features;
+        for ((info, source), (_, target)) in self.structs.iter().zip(&chain.structs) {
+            unsafe { copy_features(*target, *source, info) };
+        }
+        chain
+    }
+}
+
+impl Drop for DeviceFeaturesChain {
+    fn drop(&mut self) {
+        for (info, struct_ptr) in self.structs.drain(..) {
+            unsafe { std::alloc::dealloc(struct_ptr as *mut u8, info.layout) };
+        }
+        self.features2.p_next = null_mut();
+    }
+}
+
+/// The features of the struct at the pointer, which must be of the type of `info`
+unsafe fn features_slice<'a>(
+    struct_ptr: *const c_void,
+    info: &DeviceFeaturesStruct,
+) -> &'a [vk::Bool32] {
+    std::slice::from_raw_parts(
+        struct_ptr.add(FEATURES_OFFSET) as *const vk::Bool32,
+        info.features.len(),
+    )
+}
+
+unsafe fn features_slice_mut<'a>(
+    struct_ptr: *mut c_void,
+    info: &DeviceFeaturesStruct,
+) -> &'a mut [vk::Bool32] {
+    std::slice::from_raw_parts_mut(
+        struct_ptr.add(FEATURES_OFFSET) as *mut vk::Bool32,
+        info.features.len(),
+    )
+}
+
+unsafe fn copy_features(target: *mut c_void, source: *const c_void, info: &DeviceFeaturesStruct) {
+    features_slice_mut(target, info).copy_from_slice(features_slice(source, info));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYNC2: vk::StructureType =
+        vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES_KHR;
+    const IMAGELESS: vk::StructureType =
+        vk::StructureType::PHYSICAL_DEVICE_IMAGELESS_FRAMEBUFFER_FEATURES;
+    const VULKAN_1_2: vk::StructureType = vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_2_FEATURES;
+    const FEATURES_2: vk::StructureType = vk::StructureType::PHYSICAL_DEVICE_FEATURES_2;
+
+    /// Names of the members of a struct formatted by Debug, without the separators of the words
+    fn debug_members(debug: &str) -> Vec<String> {
+        debug
+            .split(['{', ','])
+            .filter_map(|member| member.split_once(':'))
+            .map(|(name, _)| name.trim().replace('_', ""))
+            .filter(|name| !matches!(name.as_str(), "stype" | "pnext" | "features"))
+            .collect()
+    }
+
+    #[test]
+    fn registered_features_match_the_struct_members() {
+        for info in DEVICE_FEATURES_STRUCTS {
+            let features = info
+                .features
+                .iter()
+                .map(|name| name.replace('_', "").to_lowercase())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                debug_members(&debug_default_struct(info.s_type)),
+                features,
+                "{}",
+                info.name
+            );
+            assert!(
+                FEATURES_OFFSET + features.len() * size_of::<vk::Bool32>() <= info.layout.size(),
+                "{}",
+                info.name
+            );
+        }
+    }
+
+    #[test]
+    fn registered_structs_are_unique() {
+        for (i, info) in DEVICE_FEATURES_STRUCTS.iter().enumerate() {
+            assert_eq!(
+                device_features_struct(info.s_type).map(|s| s.name),
+                Some(info.name)
+            );
+            assert!(DEVICE_FEATURES_STRUCTS[i + 1..]
+                .iter()
+                .all(|other| other.s_type != info.s_type));
+        }
+    }
+
+    #[test]
+    fn available_structs_follow_the_version_and_the_extensions() {
+        let available = |api_version, extensions: &[&str]| {
+            available_device_features_structs(api_version, extensions)
+                .map(|s| s.s_type)
+                .collect::<Vec<_>>()
+        };
+        let vulkan_1_1 = vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_1_FEATURES;
+        assert_eq!(available(vk::API_VERSION_1_1, &[]), []);
+        assert_eq!(
+            available(vk::API_VERSION_1_1, &["VK_KHR_synchronization2"]),
+            [SYNC2]
+        );
+        assert_eq!(
+            available(vk::API_VERSION_1_2, &[]),
+            [vulkan_1_1, VULKAN_1_2]
+        );
+        assert_eq!(
+            available(
+                vk::make_api_version(0, 1, 2, 203),
+                &["VK_KHR_synchronization2", "VK_KHR_imageless_framebuffer"]
+            ),
+            [vulkan_1_1, VULKAN_1_2, IMAGELESS, SYNC2]
+        );
+    }
+
+    /// The structs reached by following the p_next pointers of the chain
+    fn linked_structs(chain: &DeviceFeaturesChain) -> Vec<*const vk::BaseOutStructure> {
+        let mut structs = Vec::new();
+        let mut struct_ptr = chain.features2().p_next as *const vk::BaseOutStructure;
+        while !struct_ptr.is_null() {
+            structs.push(struct_ptr);
+            struct_ptr = unsafe { (*struct_ptr).p_next };
+        }
+        structs
+    }
+
+    #[test]
+    fn new_links_the_structs_in_order() {
+        let chain = DeviceFeaturesChain::new(&[SYNC2, VULKAN_1_2, IMAGELESS]).unwrap();
+        let s_types = linked_structs(&chain)
+            .into_iter()
+            .map(|struct_ptr| unsafe { (*struct_ptr).s_type })
+            .collect::<Vec<_>>();
+        assert_eq!(s_types, [SYNC2, VULKAN_1_2, IMAGELESS]);
+        assert_eq!(
+            chain.get(VULKAN_1_2, "subgroupBroadcastDynamicId"),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn new_rejects_unknown_and_repeated_structs() {
+        assert!(DeviceFeaturesChain::new(&[vk::StructureType::APPLICATION_INFO]).is_err());
+        assert!(DeviceFeaturesChain::new(&[SYNC2, SYNC2]).is_err());
+        assert!(DeviceFeaturesChain::new(&[FEATURES_2]).is_err());
+    }
+
+    #[test]
+    fn features_are_laid_out_like_the_structs() {
+        let mut chain = DeviceFeaturesChain::new(&[SYNC2, VULKAN_1_2]).unwrap();
+        chain.set(FEATURES_2, "inheritedQueries", true).unwrap();
+        chain.set(SYNC2, "synchronization2", true).unwrap();
+        chain
+            .set(VULKAN_1_2, "subgroupBroadcastDynamicId", true)
+            .unwrap();
+        let features2 = chain.features2();
+        assert_eq!(features2.features.inherited_queries, vk::TRUE);
+        assert_eq!(features2.features.variable_multisample_rate, vk::FALSE);
+        unsafe {
+            let sync2 =
+                &*(features2.p_next as *const vk::PhysicalDeviceSynchronization2FeaturesKHR);
+            assert_eq!(sync2.synchronization2, vk::TRUE);
+            let vulkan12 = &*(sync2.p_next as *const vk::PhysicalDeviceVulkan12Features);
+            assert_eq!(vulkan12.subgroup_broadcast_dynamic_id, vk::TRUE);
+            assert_eq!(vulkan12.shader_output_layer, vk::FALSE);
+            assert!(vulkan12.p_next.is_null());
+        }
+        assert_eq!(chain.get(VULKAN_1_2, "unknown"), None);
+        assert_eq!(chain.get(IMAGELESS, "imagelessFramebuffer"), None);
+    }
+
+    #[test]
+    fn clone_copies_the_structs_and_the_features() {
+        let mut chain = DeviceFeaturesChain::new(&[IMAGELESS, SYNC2]).unwrap();
+        chain.set(SYNC2, "synchronization2", true).unwrap();
+        chain.set(FEATURES_2, "largePoints", true).unwrap();
+        let clone = chain.clone();
+        chain.set(SYNC2, "synchronization2", false).unwrap();
+        assert_eq!(clone.features(), {
+            chain.set(SYNC2, "synchronization2", true).unwrap();
+            chain.features()
+        });
+        assert_ne!(clone.features2().p_next, chain.features2().p_next);
+        assert_eq!(
+            clone.without_features().get(SYNC2, "synchronization2"),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn set_adds_the_missing_structs() {
+        let mut chain = DeviceFeaturesChain::default();
+        chain.set(SYNC2, "synchronization2", true).unwrap();
+        assert_eq!(chain.get(SYNC2, "synchronization2"), Some(true));
+        assert!(chain.set(SYNC2, "unknown", true).is_err());
+        assert!(chain
+            .set(vk::StructureType::APPLICATION_INFO, "unknown", true)
+            .is_err());
+    }
+
+    #[test]
+    fn supports_compares_the_structs_of_the_same_type() {
+        let mut available = DeviceFeaturesChain::new(&[IMAGELESS, SYNC2]).unwrap();
+        available.set(SYNC2, "synchronization2", true).unwrap();
+        available.set(FEATURES_2, "wideLines", true).unwrap();
+        // the structs are matched by type, not by position
+        let mut desired = DeviceFeaturesChain::new(&[SYNC2]).unwrap();
+        desired.set(SYNC2, "synchronization2", true).unwrap();
+        desired.set(FEATURES_2, "wideLines", true).unwrap();
+        assert!(available.supports(&desired));
+
+        desired
+            .set(IMAGELESS, "imagelessFramebuffer", true)
+            .unwrap();
+        assert!(!available.supports(&desired));
+
+        // missing structs count as all disabled
+        let mut desired = DeviceFeaturesChain::new(&[VULKAN_1_2]).unwrap();
+        assert!(available.supports(&desired));
+        desired.set(VULKAN_1_2, "hostQueryReset", true).unwrap();
+        assert!(!available.supports(&desired));
+    }
+
+    #[test]
+    fn merge_and_intersect_combine_the_features() {
+        let mut required = DeviceFeaturesChain::new(&[IMAGELESS]).unwrap();
+        required
+            .set(IMAGELESS, "imagelessFramebuffer", true)
+            .unwrap();
+        let mut optional = DeviceFeaturesChain::new(&[SYNC2, VULKAN_1_2]).unwrap();
+        optional.set(SYNC2, "synchronization2", true).unwrap();
+        optional.set(VULKAN_1_2, "hostQueryReset", true).unwrap();
+        optional.set(FEATURES_2, "wideLines", true).unwrap();
+
+        let mut available = DeviceFeaturesChain::new(&[SYNC2]).unwrap();
+        available.set(SYNC2, "synchronization2", true).unwrap();
+        optional.intersect(&available);
+        assert_eq!(optional.get(SYNC2, "synchronization2"), Some(true));
+        assert_eq!(optional.get(VULKAN_1_2, "hostQueryReset"), Some(false));
+        assert_eq!(optional.get(FEATURES_2, "wideLines"), Some(false));
+
+        // structs without enabled features are not added
+        required.merge(&optional);
+        assert_eq!(required.get(IMAGELESS, "imagelessFramebuffer"), Some(true));
+        assert_eq!(required.get(SYNC2, "synchronization2"), Some(true));
+        assert_eq!(required.get(VULKAN_1_2, "hostQueryReset"), None);
+    }
+
+    #[test]
+    fn clone_outlives_the_original() {
+        let mut chain = DeviceFeaturesChain::new(&[IMAGELESS, SYNC2, VULKAN_1_2]).unwrap();
+        chain.set(SYNC2, "synchronization2", true).unwrap();
+        chain.set(VULKAN_1_2, "hostQueryReset", true).unwrap();
+        let clone = chain.clone();
+        let original_structs = linked_structs(&chain);
+        drop(chain);
+
+        // the clone has its own structs, still linked after the original ones are freed
+        let cloned_structs = linked_structs(&clone);
+        assert_eq!(cloned_structs.len(), 3);
+        assert!(cloned_structs
+            .iter()
+            .all(|struct_ptr| !original_structs.contains(struct_ptr)));
+        assert_eq!(clone.get(SYNC2, "synchronization2"), Some(true));
+        assert_eq!(clone.get(VULKAN_1_2, "hostQueryReset"), Some(true));
+        assert_eq!(clone.get(IMAGELESS, "imagelessFramebuffer"), Some(false));
+    }
+
+    #[test]
+    fn merged_chain_outlives_its_source() {
+        let mut required = DeviceFeaturesChain::new(&[IMAGELESS]).unwrap();
+        required
+            .set(IMAGELESS, "imagelessFramebuffer", true)
+            .unwrap();
+        let mut optional = DeviceFeaturesChain::new(&[SYNC2, VULKAN_1_2]).unwrap();
+        optional.set(SYNC2, "synchronization2", true).unwrap();
+        optional.set(VULKAN_1_2, "hostQueryReset", true).unwrap();
+        required.merge(&optional);
+        let optional_structs = linked_structs(&optional);
+        drop(optional);
+
+        // the structs added by the merge are allocated by the merged chain
+        let merged_structs = linked_structs(&required);
+        assert_eq!(merged_structs.len(), 3);
+        assert!(merged_structs
+            .iter()
+            .all(|struct_ptr| !optional_structs.contains(struct_ptr)));
+        let clone = required.clone();
+        let features = required.features();
+        drop(required);
+        assert_eq!(clone.features(), features);
+        assert_eq!(clone.get(SYNC2, "synchronization2"), Some(true));
+        assert_eq!(clone.get(VULKAN_1_2, "hostQueryReset"), Some(true));
+        drop(clone);
+    }
 }
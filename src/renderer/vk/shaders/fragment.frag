@@ -4,13 +4,19 @@ layout (location = 0) out vec4 frag_color;
 
 layout(push_constant) uniform constants {
     vec4 line_color;
+    float line_width;
+    float dash_length;
+    float gap_length;
 } pc;
 
-layout (location = 0) in FS_IN {
-    vec3 position;
+layout (location = 0) in VS_OUT {
+    float distance;
 } fs_in;
 
 void main() {
+    // a gap of 0 means a solid line
+    if (pc.gap_length > 0.0f && mod(fs_in.distance, pc.dash_length + pc.gap_length) > pc.dash_length) {
+        discard;
+    }
     frag_color = vec4(pc.line_color.xyz, 1.0f);
 }
-
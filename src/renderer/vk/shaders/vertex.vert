@@ -1,16 +1,50 @@
 #version 460
 
-layout (location = 0) in vec2 position;
+// Each instance is a segment of a line strip, both of its points are read from the same buffer
+// with an offset of one point. The z coordinate is the distance in pixels from the first point
+layout (location = 0) in vec3 point_a;
+layout (location = 1) in vec3 point_b;
 
 layout (set = 0, binding = 0) uniform uniform_buffer {
     mat4 transform;
+    vec2 viewport_size;
 };
 
+layout(push_constant) uniform constants {
+    vec4 line_color;
+    float line_width;
+    float dash_length;
+    float gap_length;
+} pc;
+
 layout (location = 0) out VS_OUT {
-    vec3 position;
+    float distance;
 } vs_out;
 
 void main() {
-    gl_Position = transform * vec4(position.x, -position.y, 0.0f, 1.0f);
-    vs_out.position = gl_Position.xyz;
-}
\ No newline at end of file
+    // Segment end points in pixels relative to the center of the viewport
+    vec2 screen_a = (transform * vec4(point_a.x, -point_a.y, 0.0f, 1.0f)).xy * viewport_size / 2.0f;
+    vec2 screen_b = (transform * vec4(point_b.x, -point_b.y, 0.0f, 1.0f)).xy * viewport_size / 2.0f;
+
+    vec2 direction = screen_b - screen_a;
+    float segment_length = length(direction);
+    direction = segment_length > 0.0f ? direction / segment_length : vec2(1.0f, 0.0f);
+    vec2 normal = vec2(-direction.y, direction.x);
+
+    // The quad is drawn as a strip of 4 vertices, even vertices are on one side of the segment and
+    // odd ones on the other. It is extended by half of the width at both ends, so that the joints
+    // between consecutive segments are covered
+    float half_width = pc.line_width / 2.0f;
+    float side = (gl_VertexIndex % 2 == 0) ? -1.0f : 1.0f;
+    vec2 screen_position;
+    if (gl_VertexIndex < 2) {
+        screen_position = screen_a - direction * half_width;
+        vs_out.distance = point_a.z - half_width;
+    } else {
+        screen_position = screen_b + direction * half_width;
+        vs_out.distance = point_b.z + half_width;
+    }
+    screen_position += normal * side * half_width;
+
+    gl_Position = vec4(screen_position * 2.0f / viewport_size, 0.0f, 1.0f);
+}
@@ -0,0 +1,433 @@
+use super::device::DeviceInfo;
+use std::fmt::{self, Write};
+
+/// Version of Vulkan, of a layer or of a device API
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LayerInfo {
+    pub name: String,
+    pub spec_version: Version,
+    pub implementation_version: u32,
+    pub description: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExtensionInfo {
+    pub name: String,
+    pub spec_version: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct MemoryHeapInfo {
+    pub size: u64,
+    pub device_local: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct QueueFamilyInfo {
+    /// Capabilities of the queues, like "GRAPHICS | COMPUTE | TRANSFER"
+    pub flags: String,
+    pub queue_count: u32,
+    pub timestamp_valid_bits: u32,
+    /// Whether the queues can present to a window, unknown without a surface
+    pub present: Option<bool>,
+}
+
+/// A features struct of a device, like VkPhysicalDeviceFeatures, with the value of each feature
+#[derive(Clone, Debug)]
+pub struct FeatureStructInfo {
+    pub name: &'static str,
+    pub features: Vec<(&'static str, bool)>,
+}
+
+/// Everything that a physical device reports, along with whether it can draw the plots
+#[derive(Clone, Debug)]
+pub struct PhysicalDeviceInfo {
+    pub device: DeviceInfo,
+    pub api_version: Version,
+    /// Version of the driver, encoded in a way that depends on the vendor
+    pub driver_version: u32,
+    pub max_image_dimension_2d: u32,
+    /// Numbers of samples per pixel supported by the color attachments
+    pub color_sample_counts: Vec<u32>,
+    pub memory_heaps: Vec<MemoryHeapInfo>,
+    pub queue_families: Vec<QueueFamilyInfo>,
+    /// Formats and color spaces of the surfaces of the windows
+    pub surface_formats: Vec<(String, String)>,
+    pub present_modes: Vec<String>,
+    pub extensions: Vec<ExtensionInfo>,
+    /// The features struct of Vulkan 1.0 followed by the ones of the extensions the device has
+    pub features: Vec<FeatureStructInfo>,
+}
+
+/**
+Report of the Vulkan implementation: the version of the instance, its layers and extensions and
+what each physical device supports. The surface formats, the present modes and the support for
+presenting of the queue families are queried with a hidden window, when one cannot be created
+`surface_error` tells why they are missing.
+*/
+#[derive(Clone, Debug)]
+pub struct VulkanInfo {
+    pub instance_version: Version,
+    pub layers: Vec<LayerInfo>,
+    pub extensions: Vec<ExtensionInfo>,
+    pub devices: Vec<PhysicalDeviceInfo>,
+    pub surface_error: Option<String>,
+}
+
+impl VulkanInfo {
+    /// The report as indented text, one item per line
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        // writing to a String cannot fail
+        let _ = self.write_text(&mut text);
+        text
+    }
+
+    fn write_text(&self, out: &mut String) -> fmt::Result {
+        writeln!(out, "Vulkan instance {}", self.instance_version)?;
+        if let Some(e) = &self.surface_error {
+            writeln!(out, "Surface: not available, {}", e)?;
+        }
+        writeln!(out, "Layers:")?;
+        for layer in &self.layers {
+            writeln!(
+                out,
+                "  {} {} (implementation {}): {}",
+                layer.name, layer.spec_version, layer.implementation_version, layer.description
+            )?;
+        }
+        writeln!(out, "Instance extensions:")?;
+        for extension in &self.extensions {
+            writeln!(out, "  {} {}", extension.name, extension.spec_version)?;
+        }
+
+        for device in &self.devices {
+            let info = &device.device;
+            let status = match &info.rejection {
+                Some(reason) => format!("rejected: {}", reason),
+                None => "usable".to_string(),
+            };
+            writeln!(
+                out,
+                "Device {}: {} ({}, vendor {:#06x}, device {:#06x}) - {}",
+                info.index,
+                info.name,
+                info.device_type.name(),
+                info.vendor_id,
+                info.device_id,
+                status
+            )?;
+            writeln!(out, "  API version: {}", device.api_version)?;
+            writeln!(out, "  Driver version: {:#x}", device.driver_version)?;
+            writeln!(out, "  Max image size: {}", device.max_image_dimension_2d)?;
+            let sample_counts = device
+                .color_sample_counts
+                .iter()
+                .map(|count| count.to_string())
+                .collect::<Vec<_>>();
+            writeln!(out, "  Color sample counts: {}", sample_counts.join(", "))?;
+            writeln!(out, "  Memory heaps:")?;
+            for (i, heap) in device.memory_heaps.iter().enumerate() {
+                let local = if heap.device_local {
+                    ", device local"
+                } else {
+                    ""
+                };
+                writeln!(out, "    {}: {} MiB{}", i, heap.size / (1024 * 1024), local)?;
+            }
+            writeln!(out, "  Queue families:")?;
+            for (i, family) in device.queue_families.iter().enumerate() {
+                let present = match family.present {
+                    Some(true) => ", presents",
+                    _ => "",
+                };
+                writeln!(
+                    out,
+                    "    {}: {}, {} queues, {} timestamp bits{}",
+                    i, family.flags, family.queue_count, family.timestamp_valid_bits, present
+                )?;
+            }
+            if self.surface_error.is_none() {
+                let formats = device
+                    .surface_formats
+                    .iter()
+                    .map(|(format, color_space)| format!("{} {}", format, color_space))
+                    .collect::<Vec<_>>();
+                writeln!(out, "  Surface formats: {}", formats.join(", "))?;
+                writeln!(out, "  Present modes: {}", device.present_modes.join(", "))?;
+            }
+            writeln!(out, "  Extensions:")?;
+            for extension in &device.extensions {
+                writeln!(out, "    {} {}", extension.name, extension.spec_version)?;
+            }
+            writeln!(out, "  Features:")?;
+            for features in &device.features {
+                writeln!(out, "    {}:", features.name)?;
+                for (name, enabled) in &features.features {
+                    writeln!(out, "      {}: {}", name, enabled)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The report as a JSON object
+    pub fn to_json(&self) -> String {
+        let layers = self.layers.iter().map(|layer| {
+            json_object(&[
+                ("name", json_string(&layer.name)),
+                ("specVersion", json_string(&layer.spec_version.to_string())),
+                (
+                    "implementationVersion",
+                    layer.implementation_version.to_string(),
+                ),
+                ("description", json_string(&layer.description)),
+            ])
+        });
+        json_object(&[
+            (
+                "instanceVersion",
+                json_string(&self.instance_version.to_string()),
+            ),
+            (
+                "surfaceError",
+                self.surface_error
+                    .as_deref()
+                    .map_or("null".to_string(), json_string),
+            ),
+            ("layers", json_array(layers)),
+            ("extensions", extensions_json(&self.extensions)),
+            ("devices", json_array(self.devices.iter().map(device_json))),
+        ])
+    }
+}
+
+fn device_json(device: &PhysicalDeviceInfo) -> String {
+    let info = &device.device;
+    let memory_heaps = device.memory_heaps.iter().map(|heap| {
+        json_object(&[
+            ("size", heap.size.to_string()),
+            ("deviceLocal", heap.device_local.to_string()),
+        ])
+    });
+    let queue_families = device.queue_families.iter().map(|family| {
+        json_object(&[
+            ("flags", json_string(&family.flags)),
+            ("queueCount", family.queue_count.to_string()),
+            (
+                "timestampValidBits",
+                family.timestamp_valid_bits.to_string(),
+            ),
+            (
+                "present",
+                family.present.map_or("null".to_string(), |p| p.to_string()),
+            ),
+        ])
+    });
+    let surface_formats = device.surface_formats.iter().map(|(format, color_space)| {
+        json_object(&[
+            ("format", json_string(format)),
+            ("colorSpace", json_string(color_space)),
+        ])
+    });
+    let features = device.features.iter().map(|features| {
+        let values = features
+            .features
+            .iter()
+            .map(|(name, enabled)| (*name, enabled.to_string()))
+            .collect::<Vec<_>>();
+        json_object(&[
+            ("name", json_string(features.name)),
+            ("features", json_object(&values)),
+        ])
+    });
+    json_object(&[
+        ("index", info.index.to_string()),
+        ("name", json_string(&info.name)),
+        ("vendorId", info.vendor_id.to_string()),
+        ("deviceId", info.device_id.to_string()),
+        ("deviceType", json_string(info.device_type.name())),
+        (
+            "rejection",
+            info.rejection
+                .as_deref()
+                .map_or("null".to_string(), json_string),
+        ),
+        ("apiVersion", json_string(&device.api_version.to_string())),
+        ("driverVersion", device.driver_version.to_string()),
+        (
+            "maxImageDimension2D",
+            device.max_image_dimension_2d.to_string(),
+        ),
+        (
+            "colorSampleCounts",
+            json_array(device.color_sample_counts.iter().map(u32::to_string)),
+        ),
+        ("memoryHeaps", json_array(memory_heaps)),
+        ("queueFamilies", json_array(queue_families)),
+        ("surfaceFormats", json_array(surface_formats)),
+        (
+            "presentModes",
+            json_array(device.present_modes.iter().map(|mode| json_string(mode))),
+        ),
+        ("extensions", extensions_json(&device.extensions)),
+        ("features", json_array(features)),
+    ])
+}
+
+fn extensions_json(extensions: &[ExtensionInfo]) -> String {
+    json_array(extensions.iter().map(|extension| {
+        json_object(&[
+            ("name", json_string(&extension.name)),
+            ("specVersion", extension.spec_version.to_string()),
+        ])
+    }))
+}
+
+/// Object with the given members, whose values are already JSON
+fn json_object(members: &[(&str, String)]) -> String {
+    let members = members
+        .iter()
+        .map(|(name, value)| format!("{}:{}", json_string(name), value))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", members.join(","))
+}
+
+/// Array of the given values, which are already JSON
+fn json_array(values: impl Iterator<Item = String>) -> String {
+    format!("[{}]", values.collect::<Vec<_>>().join(","))
+}
+
+/// The text as a JSON string, with the quotes, the backslashes and the control characters escaped
+fn json_string(text: &str) -> String {
+    let mut string = String::with_capacity(text.len() + 2);
+    string.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => string.push_str("\\\""),
+            '\\' => string.push_str("\\\\"),
+            '\n' => string.push_str("\\n"),
+            '\r' => string.push_str("\\r"),
+            '\t' => string.push_str("\\t"),
+            c if (c as u32) < 0x20 => string.push_str(&format!("\\u{:04x}", c as u32)),
+            c => string.push(c),
+        }
+    }
+    string.push('"');
+    string
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::device::DeviceType;
+
+    /// An instance with one device that has one queue family, and no window to query the surfaces
+    fn small_info() -> VulkanInfo {
+        let version = Version {
+            major: 1,
+            minor: 2,
+            patch: 203,
+        };
+        let device = DeviceInfo {
+            index: 0,
+            name: "GPU \"0\"\\A".to_string(),
+            vendor_id: 4318,
+            device_id: 7,
+            device_type: DeviceType::Discrete,
+            local_memory: 1024,
+            queue_count: 1,
+            rejection: None,
+        };
+        VulkanInfo {
+            instance_version: version,
+            layers: vec![LayerInfo {
+                name: "VK_LAYER_test".to_string(),
+                spec_version: version,
+                implementation_version: 1,
+                description: "line\nbreak\ttab\u{1}".to_string(),
+            }],
+            extensions: vec![ExtensionInfo {
+                name: "VK_KHR_surface".to_string(),
+                spec_version: 25,
+            }],
+            devices: vec![PhysicalDeviceInfo {
+                device,
+                api_version: version,
+                driver_version: 3,
+                max_image_dimension_2d: 4096,
+                color_sample_counts: vec![1, 4],
+                memory_heaps: vec![MemoryHeapInfo {
+                    size: 1024,
+                    device_local: true,
+                }],
+                queue_families: vec![QueueFamilyInfo {
+                    flags: "GRAPHICS".to_string(),
+                    queue_count: 1,
+                    timestamp_valid_bits: 64,
+                    present: None,
+                }],
+                surface_formats: Vec::new(),
+                present_modes: Vec::new(),
+                extensions: Vec::new(),
+                features: vec![FeatureStructInfo {
+                    name: "VkPhysicalDeviceFeatures",
+                    features: vec![("wideLines", false)],
+                }],
+            }],
+            surface_error: None,
+        }
+    }
+
+    #[test]
+    fn json_string_escapes_the_special_characters() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("a \"b\" c"), "\"a \\\"b\\\" c\"");
+        assert_eq!(json_string("C:\\path"), "\"C:\\\\path\"");
+        assert_eq!(json_string("a\nb\rc\td"), "\"a\\nb\\rc\\td\"");
+        assert_eq!(json_string("\u{0}\u{1f}"), "\"\\u0000\\u001f\"");
+    }
+
+    #[test]
+    fn to_json_writes_the_whole_report() {
+        let expected = concat!(
+            r#"{"instanceVersion":"1.2.203","surfaceError":null,"#,
+            r#""layers":[{"name":"VK_LAYER_test","specVersion":"1.2.203","#,
+            r#""implementationVersion":1,"description":"line\nbreak\ttab\u0001"}],"#,
+            r#""extensions":[{"name":"VK_KHR_surface","specVersion":25}],"#,
+            r#""devices":[{"index":0,"name":"GPU \"0\"\\A","vendorId":4318,"deviceId":7,"#,
+            r#""deviceType":"discrete","rejection":null,"apiVersion":"1.2.203","#,
+            r#""driverVersion":3,"maxImageDimension2D":4096,"colorSampleCounts":[1,4],"#,
+            r#""memoryHeaps":[{"size":1024,"deviceLocal":true}],"#,
+            r#""queueFamilies":[{"flags":"GRAPHICS","queueCount":1,"#,
+            r#""timestampValidBits":64,"present":null}],"#,
+            r#""surfaceFormats":[],"presentModes":[],"extensions":[],"#,
+            r#""features":[{"name":"VkPhysicalDeviceFeatures","#,
+            r#""features":{"wideLines":false}}]}]}"#,
+        );
+        assert_eq!(small_info().to_json(), expected);
+    }
+
+    #[test]
+    fn to_json_writes_the_surface_error_and_the_present_support() {
+        let mut info = small_info();
+        info.surface_error = Some("no \"display\"".to_string());
+        info.devices[0].queue_families[0].present = Some(true);
+        let json = info.to_json();
+        assert!(json.contains(r#""surfaceError":"no \"display\"""#));
+        assert!(json.contains(r#""present":true"#));
+    }
+}
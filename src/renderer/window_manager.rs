@@ -1,3 +1,4 @@
+use crate::error::FPlotError;
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 use winit::dpi::LogicalSize;
 use winit::event_loop::EventLoop;
@@ -10,20 +11,57 @@ pub struct WindowManager {
 }
 
 impl WindowManager {
-    pub fn new(resolution: (u32, u32), fullscreen: Option<Fullscreen>) -> Self {
-        let event_loop = event_loop::EventLoop::new();
+    pub fn new(resolution: (u32, u32), fullscreen: Option<Fullscreen>) -> Result<Self, FPlotError> {
+        let event_loop = create_event_loop()?;
         let window = window::WindowBuilder::new()
             .with_fullscreen(fullscreen)
-            .with_min_inner_size(LogicalSize {
+            .with_inner_size(LogicalSize {
                 width: resolution.0,
                 height: resolution.1,
             })
             .build(&event_loop)
-            .unwrap();
-        WindowManager { event_loop, window }
+            .map_err(|e| FPlotError::Window(e.to_string()))?;
+        Ok(WindowManager { event_loop, window })
+    }
+
+    /// Creates a window that is never shown, used to query what the surfaces support
+    pub fn new_hidden() -> Result<Self, FPlotError> {
+        let event_loop = create_event_loop()?;
+        let window = window::WindowBuilder::new()
+            .with_visible(false)
+            .build(&event_loop)
+            .map_err(|e| FPlotError::Window(e.to_string()))?;
+        Ok(WindowManager { event_loop, window })
     }
 
     pub fn get_window_handle(&self) -> RawWindowHandle {
         self.window.raw_window_handle()
     }
 }
+
+/// winit panics when it cannot connect to the display, so on the unix systems that use X11 or
+/// Wayland the display is looked up in the environment first and its absence is an error
+fn create_event_loop() -> Result<EventLoop<()>, FPlotError> {
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    ))]
+    {
+        let is_set = |name| {
+            std::env::var_os(name)
+                .filter(|value| !value.is_empty())
+                .is_some()
+        };
+        let display_found = match std::env::var("WINIT_UNIX_BACKEND").as_deref() {
+            Ok("x11") => is_set("DISPLAY"),
+            Ok("wayland") => is_set("WAYLAND_DISPLAY"),
+            _ => is_set("DISPLAY") || is_set("WAYLAND_DISPLAY"),
+        };
+        if !display_found {
+            return Err(FPlotError::Window(
+                "cannot create the event loop: no display available".to_string(),
+            ));
+        }
+    }
+    Ok(EventLoop::new())
+}
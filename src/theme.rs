@@ -0,0 +1,291 @@
+use std::path::{Path, PathBuf};
+
+/// RGBA color with components in the [0, 1] range
+pub type Color = [f32; 4];
+
+pub const BLACK: Color = [0.0f32, 0.0f32, 0.0f32, 1.0f32];
+pub const WHITE: Color = [1.0f32, 1.0f32, 1.0f32, 1.0f32];
+pub const GRAY: Color = [0.5f32, 0.5f32, 0.5f32, 1.0f32];
+pub const RED: Color = [0.894f32, 0.102f32, 0.110f32, 1.0f32];
+pub const GREEN: Color = [0.302f32, 0.686f32, 0.290f32, 1.0f32];
+pub const BLUE: Color = [0.216f32, 0.494f32, 0.722f32, 1.0f32];
+pub const ORANGE: Color = [1.0f32, 0.498f32, 0.0f32, 1.0f32];
+pub const PURPLE: Color = [0.596f32, 0.306f32, 0.639f32, 1.0f32];
+
+/// Okabe-Ito palette, whose colors remain distinguishable with the common forms of color
+/// blindness (black is left out since it is the background of the dark themes)
+pub const COLORBLIND_SAFE_PALETTE: [Color; 7] = [
+    [0.902f32, 0.624f32, 0.0f32, 1.0f32],
+    [0.337f32, 0.706f32, 0.914f32, 1.0f32],
+    [0.0f32, 0.620f32, 0.451f32, 1.0f32],
+    [0.941f32, 0.894f32, 0.259f32, 1.0f32],
+    [0.0f32, 0.447f32, 0.698f32, 1.0f32],
+    [0.835f32, 0.369f32, 0.0f32, 1.0f32],
+    [0.800f32, 0.475f32, 0.655f32, 1.0f32],
+];
+
+const BUILTIN_THEMES: [&str; 4] = ["dark", "light", "high_contrast", "colorblind"];
+
+/**
+Theme holds the colors used to draw the plot. The curves take the colors of the palette in order,
+starting again from the first one when there are more curves than colors.
+*/
+#[derive(Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    pub axis: Color,
+    pub grid: Color,
+    pub selection: Color,
+    pub curves: Vec<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            name: "dark".to_string(),
+            background: [0.0f32, 0.0f32, 0.0f32, 1.0f32],
+            axis: [1.0f32, 0.0f32, 0.0f32, 1.0f32],
+            grid: [0.2f32, 0.2f32, 0.2f32, 1.0f32],
+            selection: [0.5f32, 0.5f32, 0.5f32, 1.0f32],
+            curves: vec![
+                [1.0f32, 1.0f32, 1.0f32, 1.0f32],
+                [0.4f32, 0.7f32, 1.0f32, 1.0f32],
+                [1.0f32, 0.8f32, 0.3f32, 1.0f32],
+                [0.5f32, 0.9f32, 0.5f32, 1.0f32],
+            ],
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            name: "light".to_string(),
+            background: [1.0f32, 1.0f32, 1.0f32, 1.0f32],
+            axis: [0.0f32, 0.0f32, 0.0f32, 1.0f32],
+            grid: [0.85f32, 0.85f32, 0.85f32, 1.0f32],
+            selection: [0.4f32, 0.4f32, 0.4f32, 1.0f32],
+            curves: vec![
+                [0.1f32, 0.3f32, 0.8f32, 1.0f32],
+                [0.8f32, 0.2f32, 0.1f32, 1.0f32],
+                [0.1f32, 0.6f32, 0.2f32, 1.0f32],
+                [0.5f32, 0.2f32, 0.6f32, 1.0f32],
+            ],
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Theme {
+            name: "high_contrast".to_string(),
+            background: [0.0f32, 0.0f32, 0.0f32, 1.0f32],
+            axis: [1.0f32, 1.0f32, 1.0f32, 1.0f32],
+            grid: [0.45f32, 0.45f32, 0.45f32, 1.0f32],
+            selection: [1.0f32, 1.0f32, 1.0f32, 1.0f32],
+            curves: vec![
+                [1.0f32, 1.0f32, 0.0f32, 1.0f32],
+                [0.0f32, 1.0f32, 1.0f32, 1.0f32],
+                [1.0f32, 0.0f32, 1.0f32, 1.0f32],
+                [0.0f32, 1.0f32, 0.0f32, 1.0f32],
+            ],
+        }
+    }
+
+    /// The dark theme with the colorblind safe palette for the curves
+    pub fn colorblind() -> Self {
+        Theme {
+            name: "colorblind".to_string(),
+            axis: [0.8f32, 0.8f32, 0.8f32, 1.0f32],
+            curves: COLORBLIND_SAFE_PALETTE.to_vec(),
+            ..Theme::dark()
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high_contrast" => Some(Theme::high_contrast()),
+            "colorblind" => Some(Theme::colorblind()),
+            _ => None,
+        }
+    }
+
+    pub fn curve_color(&self, index: usize) -> Color {
+        self.curves[index % self.curves.len()]
+    }
+
+    /**
+    Builds a theme from a table where each color is a `"#rrggbb"` string. The `base` key names the
+    built-in theme that provides the missing colors (dark by default) and `curves` is either an
+    array of colors or `"colorblind"` for the colorblind safe palette.
+    Invalid entries are skipped and reported in the returned warnings.
+    */
+    pub fn from_table(name: &str, table: &toml::value::Table) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut theme = match table.get("base") {
+            None => Theme::dark(),
+            Some(value) => match value.as_str().and_then(Theme::builtin) {
+                Some(theme) => theme,
+                None => {
+                    warnings.push(format!(
+                        "base: expected one of {} but found {}",
+                        BUILTIN_THEMES.join(", "),
+                        value
+                    ));
+                    Theme::dark()
+                }
+            },
+        };
+        theme.name = name.to_string();
+        let mut colors = table.clone();
+        colors.remove("base");
+        warnings.extend(theme.apply_colors(&colors));
+        (theme, warnings)
+    }
+
+    /**
+    Replaces the colors given in a table, with the same keys of a theme file except `base`.
+    Invalid entries are skipped and reported in the returned warnings.
+    */
+    pub fn apply_colors(&mut self, table: &toml::value::Table) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (key, value) in table {
+            let color = match key.as_str() {
+                "background" => &mut self.background,
+                "axis" => &mut self.axis,
+                "grid" => &mut self.grid,
+                "selection" => &mut self.selection,
+                "curves" => {
+                    match curve_palette(value) {
+                        Some(curves) => self.curves = curves,
+                        None => warnings.push(format!(
+                            "curves: expected \"colorblind\" or a non empty array of colors \
+                            but found {}",
+                            value
+                        )),
+                    }
+                    continue;
+                }
+                _ => {
+                    warnings.push(format!("{}: unknown key", key));
+                    continue;
+                }
+            };
+            match value.as_str().and_then(parse_color) {
+                Some(parsed) => *color = parsed,
+                None => warnings.push(format!(
+                    "{}: expected a color like \"#rrggbb\" but found {}",
+                    key, value
+                )),
+            }
+        }
+        warnings
+    }
+
+    /// Loads a theme file, its name is the name of the file without the extension
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let table = match contents.parse::<toml::Value>().map_err(|e| e.to_string())? {
+            toml::Value::Table(table) => table,
+            _ => return Err("expected a table".to_string()),
+        };
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (theme, warnings) = Theme::from_table(&name, &table);
+        for warning in warnings {
+            eprintln!("Warning: {}: {}", path.display(), warning);
+        }
+        Ok(theme)
+    }
+}
+
+/// Parses a `"#rrggbb"` color
+fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let component = |i: usize| {
+        u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+            .ok()
+            .map(|value| value as f32 / 255.0f32)
+    };
+    Some([component(0)?, component(1)?, component(2)?, 1.0f32])
+}
+
+fn curve_palette(value: &toml::Value) -> Option<Vec<Color>> {
+    match value {
+        toml::Value::String(name) if name == "colorblind" => Some(COLORBLIND_SAFE_PALETTE.to_vec()),
+        toml::Value::Array(colors) if !colors.is_empty() => colors
+            .iter()
+            .map(|color| color.as_str().and_then(parse_color))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Directory of the custom themes, `$XDG_CONFIG_HOME/fplot/themes` on Linux
+pub fn themes_dir() -> Option<PathBuf> {
+    crate::config::config_file_path().and_then(|path| path.parent().map(|dir| dir.join("themes")))
+}
+
+/// The built-in themes, the dark one first
+pub fn builtin_themes() -> Vec<Theme> {
+    BUILTIN_THEMES
+        .iter()
+        .filter_map(|name| Theme::builtin(name))
+        .collect()
+}
+
+/// The built-in themes followed by the custom ones found in the themes directory, sorted by name
+pub fn available_themes() -> Vec<Theme> {
+    let mut themes = builtin_themes();
+    let entries = match themes_dir().and_then(|dir| std::fs::read_dir(dir).ok()) {
+        Some(entries) => entries,
+        None => return themes,
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("toml"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        match Theme::load(&path) {
+            Ok(theme) => themes.push(theme),
+            Err(e) => eprintln!("Warning: ignoring theme {}: {}", path.display(), e),
+        }
+    }
+    themes
+}
+
+/**
+Finds the theme selected with the given name among the available ones. A name that is not found is
+treated as the path of a theme file, which is loaded and added to the available themes.
+Returns the index of the theme.
+*/
+pub fn select_theme(name: &str, themes: &mut Vec<Theme>) -> Result<usize, String> {
+    if let Some(index) = themes.iter().position(|theme| theme.name == name) {
+        return Ok(index);
+    }
+    let theme = Theme::load(Path::new(name)).map_err(|e| format!("theme {}: {}", name, e))?;
+    themes.push(theme);
+    Ok(themes.len() - 1)
+}
+
+/// Formats a color as `#rrggbb`, ignoring its alpha
+pub fn color_to_hex(color: Color) -> String {
+    let component = |value: f32| (value.clamp(0.0f32, 1.0f32) * 255.0f32).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        component(color[0]),
+        component(color[1]),
+        component(color[2])
+    )
+}
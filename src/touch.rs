@@ -0,0 +1,70 @@
+use nalgebra::*;
+use std::collections::HashMap;
+use winit::event::{Touch, TouchPhase};
+
+/// Change of the view requested by a touch gesture, the pan is expressed like a mouse drag
+/// (previous position minus the new one) and the zoom is anchored at the given pixel
+pub struct GestureUpdate {
+    pub pan: Vector2<f64>,
+    pub zoom: f32,
+    pub anchor: Vector2<f64>,
+}
+
+/**
+TouchGestures keeps track of the fingers on a touch screen: one finger pans the view while two
+fingers pan it with their midpoint and zoom it with the change of their distance.
+Fingers after the second one are ignored.
+*/
+#[derive(Default)]
+pub struct TouchGestures {
+    touches: HashMap<u64, Vector2<f64>>,
+}
+
+impl TouchGestures {
+    pub fn update(&mut self, touch: &Touch) -> Option<GestureUpdate> {
+        let position = Vector2::new(touch.location.x, touch.location.y);
+        match touch.phase {
+            TouchPhase::Started => {
+                if self.touches.len() < 2 {
+                    self.touches.insert(touch.id, position);
+                }
+                None
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&touch.id);
+                None
+            }
+            TouchPhase::Moved => {
+                let previous = *self.touches.get(&touch.id)?;
+                let other = self
+                    .touches
+                    .iter()
+                    .find(|(id, _)| **id != touch.id)
+                    .map(|(_, other)| *other);
+                self.touches.insert(touch.id, position);
+                match other {
+                    None => Some(GestureUpdate {
+                        pan: previous - position,
+                        zoom: 1.0f32,
+                        anchor: position,
+                    }),
+                    Some(other) => {
+                        let previous_midpoint = (previous + other) / 2.0f64;
+                        let midpoint = (position + other) / 2.0f64;
+                        let previous_distance = (previous - other).norm();
+                        let zoom = if previous_distance > 0.0f64 {
+                            ((position - other).norm() / previous_distance) as f32
+                        } else {
+                            1.0f32
+                        };
+                        Some(GestureUpdate {
+                            pan: previous_midpoint - midpoint,
+                            zoom,
+                            anchor: midpoint,
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use nalgebra::*;
+
+// Parameters used when fitting the view to the curves: the samples taken on each curve, the
+// fraction of samples discarded as outliers at each end of the y range and the fraction of the
+// y range added as margin above and below
+const FIT_SAMPLES: usize = 1000;
+const FIT_OUTLIER_FRACTION: f32 = 0.02f32;
+const FIT_MARGIN: f32 = 0.1f32;
+
+/**
+View describes the region of the plane that is shown in the window. The center is expressed in
+world coordinates while the scale is the zoom of each axis. A scale of 1 makes the shorter side of
+the window span the [-1, 1] range and the longer side proportionally more, so with equal scales
+a world unit has the same length in pixels on both axes whatever the size of the window.
+The window size is not stored but passed to the methods that need it, so that the views kept in
+the history remain valid after a resize.
+*/
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct View {
+    pub center: Vector2<f32>,
+    pub scale: Vector2<f32>,
+    pub equal_aspect: bool,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            center: Vector2::new(0.0f32, 0.0f32),
+            scale: Vector2::new(1.0f32, 1.0f32),
+            equal_aspect: false,
+        }
+    }
+}
+
+impl View {
+    /// Half of the world space size of the window for each axis
+    pub fn half_extent(&self, window_size: Vector2<f64>) -> Vector2<f32> {
+        let aspect = aspect_ratio(window_size);
+        Vector2::new(aspect.x / self.scale.x, aspect.y / self.scale.y)
+    }
+
+    pub fn x_range(&self, window_size: Vector2<f64>) -> (f32, f32) {
+        let half_extent = self.half_extent(window_size);
+        (self.center.x - half_extent.x, self.center.x + half_extent.x)
+    }
+
+    /// Multiplies the scale of each axis by the given factor, when the aspect is locked the
+    /// factor of the axis that is being zoomed is applied to both
+    pub fn zoom(&mut self, factor: Vector2<f32>) {
+        if self.equal_aspect {
+            let uniform_factor = if factor.x != 1.0f32 {
+                factor.x
+            } else {
+                factor.y
+            };
+            self.scale *= uniform_factor;
+        } else {
+            self.scale.component_mul_assign(&factor);
+        }
+    }
+
+    /// Zooms like [`View::zoom`] but keeps the world point under the given pixel fixed
+    pub fn zoom_at(
+        &mut self,
+        factor: Vector2<f32>,
+        pixel: Vector2<f64>,
+        window_size: Vector2<f64>,
+    ) {
+        let anchor = self.screen_to_world(pixel, window_size);
+        self.zoom(factor);
+        self.center += anchor - self.screen_to_world(pixel, window_size);
+    }
+
+    /// Converts a position in pixels, with the origin at the top left of the window, to a point
+    /// in world coordinates
+    pub fn screen_to_world(&self, pixel: Vector2<f64>, window_size: Vector2<f64>) -> Vector2<f32> {
+        let normalized = Vector2::new(
+            2.0f64 * pixel.x / window_size.x - 1.0f64,
+            1.0f64 - 2.0f64 * pixel.y / window_size.y,
+        );
+        self.center
+            + normalized
+                .cast::<f32>()
+                .component_mul(&self.half_extent(window_size))
+    }
+
+    /// Makes the view show exactly the rectangle with the given opposite corners, when the aspect
+    /// is locked the rectangle is enlarged on one axis so that it fits entirely
+    pub fn zoom_to_rect(
+        &mut self,
+        corner_a: Vector2<f32>,
+        corner_b: Vector2<f32>,
+        window_size: Vector2<f64>,
+    ) {
+        let size = (corner_b - corner_a).abs();
+        let aspect = aspect_ratio(window_size);
+        self.center = (corner_a + corner_b) / 2.0f32;
+        self.scale = Vector2::new(2.0f32 * aspect.x / size.x, 2.0f32 * aspect.y / size.y);
+        if self.equal_aspect {
+            self.scale = Vector2::repeat(self.scale.min());
+        }
+    }
+
+    /// Makes the view span the given x interval and frames the y values that the curves assume
+    /// inside of it. Non finite values are ignored and the most extreme ones are discarded as
+    /// outliers, so that poles do not make the rest of the curve flat
+    pub fn fit<F: Fn(f32) -> f32>(
+        &mut self,
+        curves: &[F],
+        x_range: (f32, f32),
+        window_size: Vector2<f64>,
+    ) {
+        let (x_start, x_end) = x_range;
+        let step = (x_end - x_start) / (FIT_SAMPLES - 1) as f32;
+        let mut samples = curves
+            .iter()
+            .flat_map(|curve| (0..FIT_SAMPLES).map(move |i| curve(x_start + i as f32 * step)))
+            .filter(|y| y.is_finite())
+            .collect::<Vec<f32>>();
+
+        let (mut y_min, mut y_max) = if samples.is_empty() {
+            // Nothing to frame, so the current y range is kept
+            let half_extent = self.half_extent(window_size).y;
+            (self.center.y - half_extent, self.center.y + half_extent)
+        } else {
+            samples.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+            let outliers = (samples.len() as f32 * FIT_OUTLIER_FRACTION) as usize;
+            (samples[outliers], samples[samples.len() - 1 - outliers])
+        };
+        if y_max - y_min <= f32::EPSILON * y_max.abs().max(1.0f32) {
+            // Constant curves get a unit range around their value
+            y_min -= 0.5f32;
+            y_max += 0.5f32;
+        }
+        let margin = (y_max - y_min) * FIT_MARGIN;
+        self.zoom_to_rect(
+            Vector2::new(x_start, y_min - margin),
+            Vector2::new(x_end, y_max + margin),
+            window_size,
+        );
+    }
+
+    /// Locks or unlocks the aspect, when locking the y scale is matched with the x one
+    pub fn set_equal_aspect(&mut self, equal_aspect: bool) {
+        self.equal_aspect = equal_aspect;
+        if equal_aspect {
+            self.scale.y = self.scale.x;
+        }
+    }
+
+    /// Moves the view by a fraction of the visible range of each axis
+    pub fn pan_by_fraction(&mut self, fraction: Vector2<f32>, window_size: Vector2<f64>) {
+        self.center += 2.0f32 * fraction.component_mul(&self.half_extent(window_size));
+    }
+
+    /// Moves the view by the given amount of pixels, the movement is scaled independently on
+    /// each axis so that the plot follows the cursor (or the fingers) at any zoom level
+    pub fn pan(&mut self, delta_pixels: Vector2<f64>, window_size: Vector2<f64>) {
+        // the window spans two normalized units on each axis
+        let delta_normalized = 2.0f64 * delta_pixels.component_div(&window_size);
+        let half_extent = self.half_extent(window_size);
+        self.center.x += delta_normalized.x as f32 * half_extent.x;
+        self.center.y -= delta_normalized.y as f32 * half_extent.y;
+    }
+}
+
+/// Size of the window relative to its shorter side
+fn aspect_ratio(window_size: Vector2<f64>) -> Vector2<f32> {
+    let shorter_side = window_size.min();
+    if shorter_side > 0.0f64 {
+        (window_size / shorter_side).cast::<f32>()
+    } else {
+        // minimized windows have no meaningful aspect
+        Vector2::repeat(1.0f32)
+    }
+}
+
+const MAX_HISTORY_LEN: usize = 100;
+
+/// Back and forward stacks of the views that have been left through a navigation command
+#[derive(Default)]
+pub struct ViewHistory {
+    back: Vec<View>,
+    forward: Vec<View>,
+}
+
+impl ViewHistory {
+    /// Records the view that is being left, this discards the forward stack
+    pub fn push(&mut self, view: View) {
+        if self.back.len() == MAX_HISTORY_LEN {
+            self.back.remove(0);
+        }
+        self.back.push(view);
+        self.forward.clear();
+    }
+
+    pub fn back(&mut self, current: View) -> Option<View> {
+        let previous = self.back.pop()?;
+        self.forward.push(current);
+        Some(previous)
+    }
+
+    pub fn forward(&mut self, current: View) -> Option<View> {
+        let next = self.forward.pop()?;
+        self.back.push(current);
+        Some(next)
+    }
+}
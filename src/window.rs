@@ -0,0 +1,425 @@
+use crate::error::FPlotError;
+use crate::export::{self, pdf, raster, svg, Figure, FigureCurve};
+use crate::keybindings::Action;
+use crate::plot::{Curve, Plot};
+use crate::renderer::present_mode::PresentMode;
+use crate::renderer::vk::graph_vk::GraphVk;
+use crate::renderer::window_manager::WindowManager;
+use crate::touch::TouchGestures;
+use crate::view::{View, ViewHistory};
+
+use winit::dpi::PhysicalPosition;
+use winit::event::*;
+use winit::event_loop::ControlFlow;
+use winit::platform::run_return::EventLoopExtRunReturn;
+use winit::window::Fullscreen;
+
+use nalgebra::*;
+use winit::event::MouseScrollDelta::{LineDelta, PixelDelta};
+
+// Selections smaller than this amount of pixels on either axis are considered accidental
+const MIN_SELECTION_SIZE: f64 = 4.0f64;
+// Zoom applied for each pixel scrolled on a touchpad, in the exponent so that it is symmetric
+const PIXEL_DELTA_ZOOM_RATE: f32 = 0.005f32;
+// Fraction of the visible range moved by the pan keys and zoom applied by the zoom keys
+const KEY_PAN_FRACTION: f32 = 0.05f32;
+const KEY_ZOOM_FACTOR: f32 = 1.25f32;
+
+/// Settings of the window that shows a plot
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WindowSettings {
+    /// Size of the window in logical pixels, ignored when fullscreen
+    pub size: (u32, u32),
+    /// Whether the window covers the current monitor
+    pub fullscreen: bool,
+    pub present_mode: PresentMode,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        WindowSettings {
+            size: (800u32, 800u32),
+            fullscreen: false,
+            present_mode: PresentMode::default(),
+        }
+    }
+}
+
+fn window_size_vec(window: &winit::window::Window) -> Vector2<f64> {
+    Vector2::new(
+        window.inner_size().width as f64,
+        window.inner_size().height as f64,
+    )
+}
+
+/// Expands a zoom factor to the axes selected by the modifiers: Ctrl zooms only the x axis while
+/// Shift zooms only the y axis
+fn zoom_factor_for_modifiers(factor: f32, modifiers: ModifiersState) -> Vector2<f32> {
+    match (modifiers.ctrl(), modifiers.shift()) {
+        (true, false) => Vector2::new(factor, 1.0f32),
+        (false, true) => Vector2::new(1.0f32, factor),
+        _ => Vector2::new(factor, factor),
+    }
+}
+
+/// Closes the window with the error of the renderer, if any, and tells whether there was none
+fn check_renderer(
+    outcome: Result<(), FPlotError>,
+    result: &mut Result<(), FPlotError>,
+    control_flow: &mut ControlFlow,
+) -> bool {
+    match outcome {
+        Ok(()) => true,
+        Err(e) => {
+            *result = Err(e);
+            *control_flow = ControlFlow::Exit;
+            false
+        }
+    }
+}
+
+/// Description of the plot shown by the renderer, with the given view and size, for the exporters
+fn current_figure<'a>(
+    view: View,
+    size: Vector2<f64>,
+    gvk: &'a GraphVk,
+    curves: &'a [Curve],
+    time: f32,
+) -> Figure<'a> {
+    Figure {
+        view,
+        size,
+        theme: gvk.theme(),
+        grid: gvk.is_grid_visible(),
+        curves: curves
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| gvk.is_curve_visible(*i))
+            .map(|(i, curve)| FigureCurve {
+                function: Box::new(curve.at(time)),
+                color: gvk.curve_color(i),
+                style: gvk.curve_style(i),
+            })
+            .collect(),
+    }
+}
+
+/**
+Opens a window that shows the plot and returns when it is closed. The view can be moved with the
+mouse, the touchpad, touch gestures and the key bindings of the plot, which also switch between
+its themes, show and hide its curves and the grid and export the current view. Failing to create
+the window or the renderer, or to draw to the window later on, closes it and returns the error.
+*/
+pub fn run_window(plot: Plot) -> Result<(), FPlotError> {
+    let fullscreen = plot.window.fullscreen.then(|| Fullscreen::Borderless(None));
+    let mut window = WindowManager::new(plot.window.size, fullscreen)?;
+    let window_size = window.window.inner_size();
+    let mut gvk = GraphVk::new(
+        (window_size.width, window_size.height),
+        window.get_window_handle(),
+        plot.msaa_samples,
+        plot.window.present_mode,
+        plot.device.as_ref(),
+    )?;
+    plot.apply_to_renderer(&mut gvk)?;
+    let mut theme_index = 0;
+    let functions = plot.functions();
+    let mut view = plot.initial_view(window_size_vec(&window.window));
+    let initial_view = view;
+    let mut view_history = ViewHistory::default();
+    let mut modifiers = ModifiersState::empty();
+    let mut left_mouse_pressed = false;
+    let mut last_mouse_pressed_pos: Option<PhysicalPosition<f64>> = None;
+    let mut cursor_pos = PhysicalPosition::new(0.0f64, 0.0f64);
+    let mut selection_start: Option<PhysicalPosition<f64>> = None;
+    let mut touch_gestures = TouchGestures::default();
+    let mut result = Ok(());
+    window.event_loop.run_return(|event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => {
+                    *control_flow = ControlFlow::Exit;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            virtual_keycode: Some(vkc),
+                            state: ElementState::Pressed,
+                            ..
+                        },
+                    ..
+                } => {
+                    let window_size_vec = window_size_vec(&window.window);
+                    let redraw = match plot.key_bindings.action(vkc) {
+                        Some(Action::PanUp) => {
+                            view.pan_by_fraction(
+                                Vector2::new(0.0f32, KEY_PAN_FRACTION),
+                                window_size_vec,
+                            );
+                            true
+                        }
+                        Some(Action::PanDown) => {
+                            view.pan_by_fraction(
+                                Vector2::new(0.0f32, -KEY_PAN_FRACTION),
+                                window_size_vec,
+                            );
+                            true
+                        }
+                        Some(Action::PanLeft) => {
+                            view.pan_by_fraction(
+                                Vector2::new(-KEY_PAN_FRACTION, 0.0f32),
+                                window_size_vec,
+                            );
+                            true
+                        }
+                        Some(Action::PanRight) => {
+                            view.pan_by_fraction(
+                                Vector2::new(KEY_PAN_FRACTION, 0.0f32),
+                                window_size_vec,
+                            );
+                            true
+                        }
+                        // the zoom keys zoom both axes, the modifiers select the axes only for
+                        // the wheel since keys like '+' are typed with Shift on some layouts
+                        Some(Action::ZoomIn) => {
+                            view.zoom(Vector2::repeat(KEY_ZOOM_FACTOR));
+                            true
+                        }
+                        Some(Action::ZoomOut) => {
+                            view.zoom(Vector2::repeat(1.0f32 / KEY_ZOOM_FACTOR));
+                            true
+                        }
+                        Some(Action::Reset) => {
+                            view_history.push(view);
+                            view = initial_view;
+                            true
+                        }
+                        Some(Action::Fit) => {
+                            let visible_curves = functions
+                                .iter()
+                                .enumerate()
+                                .filter(|(i, _)| gvk.is_curve_visible(*i))
+                                .map(|(_, curve)| curve)
+                                .collect::<Vec<_>>();
+                            view_history.push(view);
+                            view.fit(
+                                &visible_curves,
+                                view.x_range(window_size_vec),
+                                window_size_vec,
+                            );
+                            true
+                        }
+                        Some(Action::ToggleEqualAspect) => {
+                            view.set_equal_aspect(!view.equal_aspect);
+                            true
+                        }
+                        Some(Action::ToggleGrid) => {
+                            let visible = !gvk.is_grid_visible();
+                            let outcome = gvk.set_grid_visible(visible);
+                            check_renderer(outcome, &mut result, control_flow)
+                        }
+                        Some(Action::CycleTheme) => {
+                            theme_index = (theme_index + 1) % plot.themes.len();
+                            let outcome = gvk.set_theme(plot.themes[theme_index].clone());
+                            check_renderer(outcome, &mut result, control_flow)
+                        }
+                        Some(Action::ViewBack) => match view_history.back(view) {
+                            Some(previous) => {
+                                view = previous;
+                                true
+                            }
+                            None => false,
+                        },
+                        Some(Action::ViewForward) => match view_history.forward(view) {
+                            Some(next) => {
+                                view = next;
+                                true
+                            }
+                            None => false,
+                        },
+                        Some(Action::ToggleCurve(i)) if i < plot.curves.len() => {
+                            let visible = !gvk.is_curve_visible(i);
+                            let outcome = gvk.set_curve_visible(i, visible);
+                            check_renderer(outcome, &mut result, control_flow)
+                        }
+                        Some(Action::Screenshot) => {
+                            let path = export::timestamped_path("png");
+                            let saved = gvk.screenshot().map_err(|e| e.to_string()).and_then(
+                                |(width, height, pixels)| {
+                                    raster::write_png(&path, width, height, &pixels)
+                                },
+                            );
+                            match saved {
+                                Ok(()) => println!("Saved {}", path.display()),
+                                Err(e) => eprintln!("Error: {}", e),
+                            }
+                            false
+                        }
+                        Some(Action::ExportSvg) => {
+                            let figure = current_figure(
+                                view,
+                                window_size_vec,
+                                &gvk,
+                                &plot.curves,
+                                plot.time,
+                            );
+                            let path = export::timestamped_path("svg");
+                            match svg::write_svg(&path, &figure) {
+                                Ok(()) => println!("Saved {}", path.display()),
+                                Err(e) => eprintln!("Error: {}", e),
+                            }
+                            false
+                        }
+                        Some(Action::ExportPdf) => {
+                            // the page shows the same region of the plane as the window, stretched
+                            // to the plot area of the page unless the aspect is locked
+                            let page = plot.pdf_page;
+                            let size = page.plot_size();
+                            let mut page_view = view;
+                            page_view.zoom_to_rect(
+                                view.screen_to_world(Vector2::zeros(), window_size_vec),
+                                view.screen_to_world(window_size_vec, window_size_vec),
+                                size,
+                            );
+                            let figure =
+                                current_figure(page_view, size, &gvk, &plot.curves, plot.time);
+                            let path = export::timestamped_path("pdf");
+                            match pdf::write_pdf(&path, &figure, &page) {
+                                Ok(()) => println!("Saved {}", path.display()),
+                                Err(e) => eprintln!("Error: {}", e),
+                            }
+                            false
+                        }
+                        _ => false,
+                    };
+                    if redraw {
+                        window.window.request_redraw();
+                    }
+                }
+                // The selection of a region to zoom into is done with the right button or while
+                // holding Shift with the left one
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button: MouseButton::Right,
+                    ..
+                } => selection_start = Some(cursor_pos),
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button: MouseButton::Left,
+                    ..
+                } if modifiers.shift() => selection_start = Some(cursor_pos),
+                WindowEvent::MouseInput {
+                    state: ElementState::Released,
+                    button: MouseButton::Left | MouseButton::Right,
+                    ..
+                } if selection_start.is_some() => {
+                    let start = selection_start.take().unwrap();
+                    gvk.set_selection(None);
+                    if (start.x - cursor_pos.x).abs() > MIN_SELECTION_SIZE
+                        && (start.y - cursor_pos.y).abs() > MIN_SELECTION_SIZE
+                    {
+                        let window_size_vec = window_size_vec(&window.window);
+                        let corner_a =
+                            view.screen_to_world(Vector2::new(start.x, start.y), window_size_vec);
+                        let corner_b = view.screen_to_world(
+                            Vector2::new(cursor_pos.x, cursor_pos.y),
+                            window_size_vec,
+                        );
+                        view_history.push(view);
+                        view.zoom_to_rect(corner_a, corner_b, window_size_vec);
+                    }
+                    window.window.request_redraw();
+                }
+                WindowEvent::MouseInput {
+                    state: pressed_state,
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    left_mouse_pressed = match pressed_state {
+                        ElementState::Pressed => true,
+                        ElementState::Released => {
+                            last_mouse_pressed_pos = None;
+                            false
+                        }
+                    }
+                }
+                WindowEvent::CursorMoved { position: pos, .. } => {
+                    if let Some(start) = selection_start {
+                        let window_size_vec = window_size_vec(&window.window);
+                        gvk.set_selection(Some([
+                            view.screen_to_world(Vector2::new(start.x, start.y), window_size_vec),
+                            view.screen_to_world(Vector2::new(pos.x, pos.y), window_size_vec),
+                        ]));
+                        window.window.request_redraw();
+                    } else if last_mouse_pressed_pos.is_some() && left_mouse_pressed {
+                        let ex_pos_vec = Vector2::new(
+                            last_mouse_pressed_pos.unwrap().x,
+                            last_mouse_pressed_pos.unwrap().y,
+                        );
+                        let pos_vec = Vector2::new(pos.x, pos.y);
+
+                        view.pan(ex_pos_vec - pos_vec, window_size_vec(&window.window));
+                        window.window.request_redraw();
+                    }
+                    last_mouse_pressed_pos = Some(pos);
+                    cursor_pos = pos;
+                }
+                WindowEvent::Resized(_) => window.window.request_redraw(),
+                WindowEvent::ModifiersChanged(state) => modifiers = state,
+                WindowEvent::MouseWheel { delta: val, .. } => {
+                    match val {
+                        LineDelta(_, y) => {
+                            view.zoom_at(
+                                zoom_factor_for_modifiers(1.0f32 + y * 0.1f32, modifiers),
+                                Vector2::new(cursor_pos.x, cursor_pos.y),
+                                window_size_vec(&window.window),
+                            );
+                        }
+                        // Touchpads scroll by pixels: plain scrolling pans the view while
+                        // scrolling with Ctrl and/or Shift held zooms it
+                        PixelDelta(delta) => {
+                            if modifiers.ctrl() || modifiers.shift() {
+                                let factor = (delta.y as f32 * PIXEL_DELTA_ZOOM_RATE).exp();
+                                view.zoom_at(
+                                    zoom_factor_for_modifiers(factor, modifiers),
+                                    Vector2::new(cursor_pos.x, cursor_pos.y),
+                                    window_size_vec(&window.window),
+                                );
+                            } else {
+                                view.pan(
+                                    Vector2::new(-delta.x, -delta.y),
+                                    window_size_vec(&window.window),
+                                );
+                            }
+                        }
+                    }
+                    window.window.request_redraw();
+                }
+                WindowEvent::Touch(touch) => {
+                    if let Some(gesture) = touch_gestures.update(&touch) {
+                        let window_size_vec = window_size_vec(&window.window);
+                        view.pan(gesture.pan, window_size_vec);
+                        view.zoom_at(
+                            Vector2::repeat(gesture.zoom),
+                            gesture.anchor,
+                            window_size_vec,
+                        );
+                        window.window.request_redraw();
+                    }
+                }
+                _ => (),
+            },
+            Event::RedrawRequested(_) => {
+                let window_size_vec = window_size_vec(&window.window);
+                let (x_start, x_end) = view.x_range(window_size_vec);
+                gvk.fill_graph_buffer(x_start, x_end, &functions);
+                gvk.set_transform(&view.center, &view.half_extent(window_size_vec));
+                let outcome = gvk.present_loop(&window.window);
+                check_renderer(outcome, &mut result, control_flow);
+            }
+            _ => (),
+        }
+    });
+    result
+}